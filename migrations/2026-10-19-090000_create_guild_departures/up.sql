@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+create table guild_departures
+(
+    guild_id bigint
+        constraint guild_departures_pk
+            primary key,
+    departed_at bigint not null
+);
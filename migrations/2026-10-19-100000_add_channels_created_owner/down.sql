@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+alter table channels_created
+    drop column owner_id;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+alter table channels_created
+    add owner_id bigint;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+alter table presets
+    drop column author_id,
+    drop column locked,
+    drop column allowed_roles;
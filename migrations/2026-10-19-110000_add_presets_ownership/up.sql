@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+alter table presets
+    add author_id bigint,
+    add locked boolean not null default false,
+    add allowed_roles bigint[] not null default '{}';
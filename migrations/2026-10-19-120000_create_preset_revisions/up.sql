@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+create table preset_revisions
+(
+    guild_id bigint,
+    preset_name varchar,
+    revision int,
+    preset_data jsonb not null,
+    author_id bigint,
+    created_at bigint not null,
+
+    constraint preset_revisions_pk
+        primary key (guild_id, preset_name, revision)
+);
+
+insert into preset_revisions (guild_id, preset_name, revision, preset_data, author_id, created_at)
+    select guild_id, preset_name, 1, preset_data, author_id, extract(epoch from now())::bigint
+    from presets;
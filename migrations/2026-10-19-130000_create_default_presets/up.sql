@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+create table default_presets
+(
+    guild_id bigint,
+    scope_id bigint,
+    preset_name varchar not null,
+
+    constraint default_presets_pk
+        primary key (guild_id, scope_id)
+);
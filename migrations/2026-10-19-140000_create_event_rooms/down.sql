@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+alter table channels_created
+    drop column event_id;
+
+drop table event_rooms cascade;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+create table event_rooms
+(
+    guild_id bigint
+        constraint event_rooms_pk
+            primary key,
+    preset_name varchar not null,
+    lead_time int not null,
+    category_id bigint
+);
+
+alter table channels_created
+    add event_id bigint;
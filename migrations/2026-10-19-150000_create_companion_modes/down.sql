@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+alter table channels_created
+    drop column companion_id;
+
+drop table companion_modes cascade;
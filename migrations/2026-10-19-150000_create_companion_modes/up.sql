@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+create table companion_modes
+(
+    guild_id bigint
+        constraint companion_modes_pk
+            primary key,
+    companion_mode varchar not null
+);
+
+alter table channels_created
+    add companion_id bigint;
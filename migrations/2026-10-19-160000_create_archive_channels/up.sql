@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+create table archive_channels
+(
+    guild_id bigint
+        constraint archive_channels_pk
+            primary key,
+    channel_id bigint not null
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+create table room_limits
+(
+    guild_id bigint
+        constraint room_limits_pk
+            primary key,
+    per_member int,
+    per_category int,
+    per_guild int
+);
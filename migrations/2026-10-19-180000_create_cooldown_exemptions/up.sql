@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+create table cooldown_exemptions
+(
+    guild_id bigint,
+    role_id bigint,
+
+    constraint cooldown_exemptions_pk
+        primary key (guild_id, role_id)
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+create table overflow_categories
+(
+    guild_id bigint,
+    category_id bigint,
+    overflow_id bigint not null,
+
+    constraint overflow_categories_pk
+        primary key (guild_id, category_id)
+);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+create table build_categories
+(
+    guild_id bigint,
+    category_id bigint,
+
+    constraint build_categories_pk
+        primary key (guild_id, category_id)
+);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+create table category_orderings
+(
+    guild_id bigint,
+    category_id bigint,
+    ordering varchar not null,
+    anchor_id bigint,
+
+    constraint category_orderings_pk
+        primary key (guild_id, category_id)
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+create table command_visibilities
+(
+    guild_id bigint,
+    command varchar,
+    ephemeral boolean not null,
+
+    constraint command_visibilities_pk
+        primary key (guild_id, command)
+);
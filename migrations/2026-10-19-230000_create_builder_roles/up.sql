@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+create table builder_roles
+(
+    guild_id bigint,
+    role_id bigint,
+
+    constraint builder_roles_pk
+        primary key (guild_id, role_id)
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+drop table build_bans cascade;
+drop table blocked_names cascade;
+drop table moderation_channels cascade;
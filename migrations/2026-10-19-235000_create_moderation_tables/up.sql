@@ -0,0 +1,29 @@
+-- Your SQL goes here
+
+create table build_bans
+(
+    guild_id bigint,
+    user_id bigint,
+    expires_at bigint,
+    reason varchar,
+
+    constraint build_bans_pk
+        primary key (guild_id, user_id)
+);
+
+create table blocked_names
+(
+    guild_id bigint,
+    pattern varchar,
+
+    constraint blocked_names_pk
+        primary key (guild_id, pattern)
+);
+
+create table moderation_channels
+(
+    guild_id bigint
+        constraint moderation_channels_pk
+            primary key,
+    channel_id bigint not null
+);
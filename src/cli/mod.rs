@@ -0,0 +1,98 @@
+//! This module contains the administration commands which can be run from the command line of the bot host.
+//!
+//! ```console
+//! $ bobbot guilds list
+//! $ bobbot guilds purge
+//! ```
+
+use std::collections::{BTreeSet};
+use std::time::{UNIX_EPOCH};
+use serenity::http::{Http, GuildPagination};
+use serenity::model::prelude::{GuildId};
+use crate::errors::*;
+use crate::database::models::{WithGuildDeparture, WithGuildData, get_known_guilds};
+
+
+/// Run the command line command described by `args`, not including the name of the executable.
+pub async fn run(token: &str, args: &[String]) -> BobResult<()> {
+    let http = Http::new(token);
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+
+    match args.as_slice() {
+        ["guilds", "list"] => command_guilds_list(&http).await,
+        ["guilds", "purge"] => command_guilds_purge(&http).await,
+        _ => Err(BobError::from_msg(ErrorKind::Host, "Unknown command; available commands are `guilds list` and `guilds purge`")),
+    }
+}
+
+
+/// Get all the guilds the bot is currently a member of.
+async fn current_guilds(http: &Http) -> BobResult<BTreeSet<GuildId>> {
+    let mut result = BTreeSet::new();
+    let mut after: Option<GuildPagination> = None;
+
+    loop {
+        let page = http.get_guilds(after.as_ref(), Some(200))
+            .await.bob_catch(ErrorKind::External, "Couldn't retrieve the guilds of the bot")?;
+
+        match page.last() {
+            None => break,
+            Some(last) => after = Some(GuildPagination::After(last.id)),
+        }
+
+        result.extend(page.iter().map(|g| g.id));
+    }
+
+    Ok(result)
+}
+
+
+/// Get the guilds which have data stored in the database, but which the bot is not a member of anymore.
+async fn orphaned_guilds(http: &Http) -> BobResult<Vec<GuildId>> {
+    let current = current_guilds(http).await?;
+
+    Ok(
+        get_known_guilds()?
+            .into_iter()
+            .filter(|g| !current.contains(g))
+            .collect()
+    )
+}
+
+
+/// List the guilds the bot no longer belongs to.
+async fn command_guilds_list(http: &Http) -> BobResult<()> {
+    let orphaned = orphaned_guilds(http).await?;
+
+    if orphaned.is_empty() {
+        println!("No orphaned guilds found.");
+        return Ok(());
+    }
+
+    for guild_id in orphaned {
+        match guild_id.get_departure()? {
+            Some(departed_at) => println!(
+                "{}\tdeparted at {}",
+                &guild_id,
+                departed_at.duration_since(UNIX_EPOCH).bob_catch(ErrorKind::Host, "System time is before the UNIX epoch.")?.as_secs()
+            ),
+            None => println!("{}\tdeparture unknown", &guild_id),
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Purge the data of all the guilds the bot no longer belongs to, without waiting for the grace period.
+async fn command_guilds_purge(http: &Http) -> BobResult<()> {
+    let orphaned = orphaned_guilds(http).await?;
+
+    for guild_id in &orphaned {
+        guild_id.purge_guild_data()?;
+        println!("{}\tpurged", &guild_id);
+    }
+
+    println!("Purged {} orphaned guilds.", orphaned.len());
+    Ok(())
+}
@@ -3,39 +3,147 @@ use serenity::model::prelude::*;
 use serenity::model::application::interaction::application_command::CommandData;
 use crate::extensions::*;
 use crate::errors::*;
-use crate::tasks::build::task_build;
+use crate::tasks::build::{task_build, BuildPreset, BuildOptions};
 use crate::tasks::mov::task_move;
 use crate::utils::channel_names::{Channelizable};
+use crate::database::models::{MayHaveBeenCreatedByBob, WithBuildCategories};
+use crate::utils::permission_overwrites::{RoomAccess};
+
+
+/// The names of the `invite` options of the build command.
+pub const INVITE_OPTIONS: [&str; 5] = ["invite1", "invite2", "invite3", "invite4", "invite5"];
+
+
+/// Mention the target of a [PermissionOverwriteType].
+fn mention_target(target: &PermissionOverwriteType) -> String {
+    match target {
+        PermissionOverwriteType::Member(user_id) => user_id.mention().to_string(),
+        PermissionOverwriteType::Role(role_id) => role_id.mention().to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+
+/// Ensure that channels can be built in the `requested` category, or in the `current` category of the channel where the
+/// command was used if none was requested, according to the build categories of the guild.
+///
+/// If no build category has been set, channels can only be built in the `current` category.
+fn check_build_category(guild_id: GuildId, requested: Option<ChannelId>, current: &Option<ChannelCategory>) -> BobResult<()> {
+    let allowed = guild_id.get_build_categories()?;
+    let current = current.as_ref().map(|c| c.id);
+
+    if allowed.is_empty() {
+        return match requested {
+            Some(requested) if Some(requested) != current => Err(BobError::from_msg(
+                ErrorKind::User, "Channels can only be built in the category of this channel."
+            )),
+            _ => Ok(()),
+        };
+    }
+
+    match requested.or(current) {
+        Some(category) if allowed.contains(&category) => Ok(()),
+        _ => {
+            let allowed: Vec<String> = allowed.iter().map(|c| c.mention().to_string()).collect();
+            Err(BobError::from_msg(ErrorKind::User, &format!(
+                "Channels can't be built here: use the `category` option to build in {}.", allowed.join(", ")
+            )))
+        },
+    }
+}
 
 
 pub async fn command_build(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
     debug!("Called command: build");
 
     let guild = guild_id.ext_partial_guild(&ctx.http).await?;
-    let category = channel_id
+
+    let options = data.to_owned().options.option_hashmap();
+    let current = channel_id
         .ext_guild_channel(&ctx.http).await?
         .ext_category(&ctx.http).await?;
+    let requested = options.opt_channel("category")?;
+    check_build_category(guild_id, requested.as_ref().map(|c| c.id), &current)?;
+
+    let category = match requested {
+        Some(category) => Some(
+            category.id
+                .to_channel(&ctx.http)
+                .await
+                .bob_catch(ErrorKind::External, "Couldn't retrieve channel info")?
+                .category()
+                .bob_catch(ErrorKind::User, "Channels can only be built in categories.")?
+        ),
+        None => current,
+    };
 
-    let options = data.to_owned().options.option_hashmap();
     let name = options.req_string("name")?.channelify();
     let preset = options.opt_string("preset")?.map(|p| p.channelify());
+    let bare = options.opt_boolean("bare")?.unwrap_or(false);
+    let private = options.opt_boolean("private")?.unwrap_or(false);
+
+    let mut invitees: Vec<PermissionOverwriteType> = vec![];
+    for option in INVITE_OPTIONS {
+        if let Some(invitee) = options.opt_mentionable(option)? {
+            if !invitees.contains(&invitee) {
+                invitees.push(invitee);
+            }
+        }
+    }
     let kind = match options.opt_string("kind")? {
         Some(s) => match s.as_str() {
-            "Voice" => ChannelType::Voice,
-            "Stage" => ChannelType::Stage,
+            "Voice" => Some(ChannelType::Voice),
+            "Stage" => Some(ChannelType::Stage),
             _ => return Err(
                 BobError { knd: ErrorKind::Developer, msg: Some("Invalid channel kind.".to_string()), err: None }
             )
         }
-        None => ChannelType::Voice,
+        None => None,
+    };
+
+    let preset = match (&preset, bare) {
+        (Some(_), true) => return Err(
+            BobError::from_msg(ErrorKind::User, "You can't specify a preset while building a bare channel.")
+        ),
+        (Some(preset), false) => BuildPreset::Named(preset),
+        (None, true) => BuildPreset::Bare,
+        (None, false) => BuildPreset::Default,
+    };
+
+    let build_options = BuildOptions {
+        kind,
+        preset,
+        access: RoomAccess { private, invitees },
+        event: None,
     };
 
-    let created = task_build(
-        ctx, &guild, &name, kind, member, &category,
-        &preset.as_deref()
-    ).await?;
+    let built = task_build(ctx, &guild, &name, member, &category, &build_options).await?;
+    let created = built.channel;
+    let access = &build_options.access;
 
     let _ = task_move(ctx, &guild, member.user.id, created.id).await;
 
-    Ok(format!("🔨 Built temporary voice channel {}!", &created.mention()))
+    let mut lines = vec![match created.get_companion()? {
+        Some(companion) => format!("🔨 Built temporary voice channel {}, with companion {}!", &created.mention(), &companion.mention()),
+        None => format!("🔨 Built temporary voice channel {}!", &created.mention()),
+    }];
+
+    if access.private {
+        let mut joiners = vec![member.mention().to_string()];
+        joiners.extend(access.invitees.iter().map(mention_target));
+        lines.push(format!("🔒 It's private: only {} can join it.", joiners.join(", ")));
+    }
+    else if !access.invitees.is_empty() {
+        let invitees: Vec<String> = access.invitees.iter().map(mention_target).collect();
+        lines.push(format!("✉ {} can join it too.", invitees.join(", ")));
+    }
+
+    if !built.ungranted.is_empty() {
+        lines.push(format!(
+            "⚠️ I couldn't set these permissions on the channel, as I don't have them myself: {}.",
+            built.ungranted.get_permission_names().join(", ")
+        ));
+    }
+
+    Ok(lines.join("\n"))
 }
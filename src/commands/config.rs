@@ -1,10 +1,18 @@
 use serenity::prelude::*;
 use serenity::model::prelude::*;
-use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::application::interaction::application_command::{ApplicationCommandInteraction, CommandDataOption};
 use crate::extensions::*;
 use crate::errors::*;
-use crate::database::models::{WithDeletionTime, WithCommandChannel};
-use std::time::Duration;
+use crate::database::models::{WithDeletionTime, WithCommandChannel, WithDefaultPreset, CanGetPresetData, WithEventRooms, WithCompanionMode, CompanionMode, WithArchiveChannel, WithRoomLimits, WithCooldownExemptions, WithOverflowCategories, WithBuildCategories, WithCategoryOrderings, RoomOrdering, WithCommandVisibilities, WithBuilderRoles, WithBuildBans, WithNameBlocklist, WithModerationChannel};
+use crate::tasks::archive::{transcripts_enabled};
+use crate::tasks::event::{schedule_event_rooms};
+use crate::tasks::order::{task_order};
+use crate::utils::channel_names::{Channelizable};
+use crate::utils::guild_export::{GuildExport, ExportFormat};
+use crate::utils::doctor::{diagnose};
+use crate::utils::moderation::{compile_pattern, log_moderation};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::convert::{TryFrom};
 
 
 pub async fn command_config_cc(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
@@ -46,4 +54,684 @@ pub async fn command_config_dt(_ctx: &Context, guild_id: GuildId, _channel_id: C
     guild_id.set_deletion_time(Duration::from_secs(timeout.unsigned_abs()))?;
 
     Ok(format!("🔧 Deletion time set to **{} seconds**!", &timeout))
-}
\ No newline at end of file
+}
+
+
+pub async fn command_config_preset(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config preset");
+
+    let options = data.to_owned().option_hashmap();
+
+    let preset = options.opt_string("preset")?.map(|p| p.channelify());
+    let category = options.opt_channel("category")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to change the default preset."))
+    }
+
+    if let Some(category) = &category {
+        if category.kind != ChannelType::Category {
+            return Err(BobError::from_msg(ErrorKind::User, "Default presets can only be set for categories."))
+        }
+    }
+
+    let scope = match &category {
+        Some(category) => format!("the category **{}**", category.name.as_deref().unwrap_or("?")),
+        None => "the whole server".to_string(),
+    };
+    let category = category.map(|c| c.id);
+
+    match preset {
+        Some(preset) => {
+            guild_id.get_preset(&preset)?
+                .bob_catch(ErrorKind::User, "No such preset.")?;
+
+            guild_id.set_default_preset(category, preset.clone())?;
+
+            Ok(format!("🔧 Default preset of {} set to `{}`!", &scope, &preset))
+        },
+        None => {
+            guild_id.unset_default_preset(category)?;
+
+            Ok(format!("🔧 Default preset of {} unset!", &scope))
+        },
+    }
+}
+
+pub async fn command_config_limits(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config limits");
+
+    let options = data.to_owned().option_hashmap();
+
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to change the limits on temporary channels."))
+    }
+
+    // Omitted limits are kept as they are, while 0 removes a limit
+    let limit = |name: &'static str, current: Option<i32>| -> BobResult<Option<u32>> {
+        match options.opt_integer(name)? {
+            None => Ok(current.and_then(|v| u32::try_from(v).ok())),
+            Some(0) => Ok(None),
+            Some(v) => Ok(Some(u32::try_from(v).bob_catch(ErrorKind::User, "Limits must be positive.")?)),
+        }
+    };
+    let current = guild_id.get_room_limits()?;
+    let per_member = limit("member", current.as_ref().and_then(|l| l.per_member))?;
+    let per_category = limit("category", current.as_ref().and_then(|l| l.per_category))?;
+    let per_guild = limit("server", current.as_ref().and_then(|l| l.per_guild))?;
+
+    guild_id.set_room_limits(per_member, per_category, per_guild)?;
+
+    let describe = |limit: Option<u32>| limit.map_or_else(|| "no limit".to_string(), |l| format!("**{}**", l));
+
+    Ok(format!(
+        "🔧 Limits on temporary channels set!\nPer member: {}\nPer category: {}\nPer server: {}",
+        describe(per_member),
+        describe(per_category),
+        describe(per_guild),
+    ))
+}
+
+
+pub async fn command_config_builders(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config builders");
+
+    let options = data.to_owned().option_hashmap();
+
+    let action = options.req_string("action")?;
+    let role = options.opt_role("role")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to change who can build channels."))
+    }
+
+    match (action.as_str(), role) {
+        ("add", Some(role)) => { guild_id.set_builder_role(role.id, true)?; },
+        ("remove", Some(role)) => { guild_id.set_builder_role(role.id, false)?; },
+        ("add", None) | ("remove", None) => {
+            return Err(BobError::from_msg(ErrorKind::User, "You need to specify the role to add or remove."))
+        },
+        ("everyone", _) => { guild_id.unset_builder_roles()?; },
+        _ => {
+            return Err(BobError::from_msg(ErrorKind::Developer, "Invalid builders action."))
+        },
+    }
+
+    let roles: Vec<String> = guild_id.get_builder_roles()?
+        .iter()
+        .map(|r| r.mention().to_string())
+        .collect();
+
+    match roles.is_empty() {
+        true => Ok("🔧 Everyone can now build channels!".to_string()),
+        false => Ok(format!("🔧 Only members of {} can now build channels!", roles.join(", "))),
+    }
+}
+
+
+pub async fn command_config_categories(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config categories");
+
+    let options = data.to_owned().option_hashmap();
+
+    let category = options.req_channel("category")?;
+    let allowed = options.req_boolean("allowed")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to change where channels can be built."))
+    }
+
+    if category.kind != ChannelType::Category {
+        return Err(BobError::from_msg(ErrorKind::User, "Only Categories can be allowed."))
+    }
+
+    guild_id.set_build_category(category.id, allowed)?;
+
+    let categories: Vec<String> = guild_id.get_build_categories()?
+        .iter()
+        .map(|c| c.mention().to_string())
+        .collect();
+
+    match categories.is_empty() {
+        true => Ok("🔧 Channels can now be built in any category!".to_string()),
+        false => Ok(format!("🔧 Channels can now only be built in {}!", categories.join(", "))),
+    }
+}
+
+
+pub async fn command_config_overflow(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config overflow");
+
+    let options = data.to_owned().option_hashmap();
+
+    let category = options.req_channel("category")?;
+    let overflow = options.opt_channel("overflow")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to change the overflow categories."))
+    }
+
+    if category.kind != ChannelType::Category {
+        return Err(BobError::from_msg(ErrorKind::User, "Only Categories can have an overflow category."))
+    }
+
+    match overflow {
+        Some(overflow) => {
+            if overflow.kind != ChannelType::Category {
+                return Err(BobError::from_msg(ErrorKind::User, "Only Categories are valid overflow categories."))
+            }
+
+            if overflow.id == category.id {
+                return Err(BobError::from_msg(ErrorKind::User, "A category can't be its own overflow category."))
+            }
+
+            guild_id.edit_overflow_category(category.id, Some(overflow.id))?;
+
+            Ok(format!("🔧 Channels will be built in {} when {} is full!", &overflow.id.mention(), &category.id.mention()))
+        },
+        None => {
+            guild_id.edit_overflow_category(category.id, None)?;
+
+            Ok(format!("🔧 {} doesn't have an overflow category anymore!", &category.id.mention()))
+        },
+    }
+}
+
+
+pub async fn command_config_order(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config order");
+
+    let options = data.to_owned().option_hashmap();
+
+    let category = options.req_channel("category")?;
+    let ordering = options.opt_string("ordering")?.map(|o| RoomOrdering::parse(&o)).transpose()?;
+    let anchor = options.opt_channel("anchor")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to change the ordering of temporary channels."))
+    }
+
+    if category.kind != ChannelType::Category {
+        return Err(BobError::from_msg(ErrorKind::User, "Only Categories can be ordered."))
+    }
+
+    let anchor = match (ordering, anchor) {
+        (Some(RoomOrdering::Anchor), Some(anchor)) => {
+            let anchor = anchor.id.ext_guild_channel(&ctx.http).await?;
+            if anchor.parent_id != Some(category.id) {
+                return Err(BobError::from_msg(ErrorKind::User, "The anchor channel must be in the category."))
+            }
+            Some(anchor.id)
+        },
+        (Some(RoomOrdering::Anchor), None) => {
+            return Err(BobError::from_msg(ErrorKind::User, "You need to specify the anchor channel to place temporary channels below."))
+        },
+        (_, Some(_)) => {
+            return Err(BobError::from_msg(ErrorKind::User, "An anchor channel can only be specified with the anchor ordering."))
+        },
+        (_, None) => None,
+    };
+
+    let response = match ordering {
+        Some(ordering) => {
+            guild_id.set_category_ordering(category.id, ordering, anchor)?;
+
+            match (ordering, anchor) {
+                (RoomOrdering::Anchor, Some(anchor)) => format!("🔧 Temporary channels in {} will be placed below {}!", &category.id.mention(), &anchor.mention()),
+                (RoomOrdering::Alphabetical, _) => format!("🔧 Temporary channels in {} will be sorted alphabetically!", &category.id.mention()),
+                _ => format!("🔧 Temporary channels in {} will be sorted by creation time!", &category.id.mention()),
+            }
+        },
+        None => {
+            guild_id.unset_category_ordering(category.id)?;
+
+            format!("🔧 Temporary channels in {} won't be sorted anymore!", &category.id.mention())
+        },
+    };
+
+    task_order(ctx, guild_id, Some(category.id)).await?;
+
+    Ok(response)
+}
+
+
+pub async fn command_config_cooldown(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config cooldown");
+
+    let options = data.to_owned().option_hashmap();
+
+    let role = options.req_role("role")?;
+    let exempt = options.req_boolean("exempt")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to change the cooldown exemptions."))
+    }
+
+    guild_id.set_cooldown_exemption(role.id, exempt)?;
+
+    match exempt {
+        true => Ok(format!("🔧 Members of {} are now exempt from cooldowns!", &role.mention())),
+        false => Ok(format!("🔧 Members of {} are not exempt from cooldowns anymore!", &role.mention())),
+    }
+}
+
+
+pub async fn command_config_visibility(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config visibility");
+
+    let options = data.to_owned().option_hashmap();
+
+    let command = options.req_string("command")?;
+    let ephemeral = options.opt_boolean("ephemeral")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to change the visibility of responses."))
+    }
+
+    guild_id.edit_command_visibility(&command, ephemeral)?;
+
+    match ephemeral {
+        Some(true) => Ok(format!("🔧 Responses to **{}** will only be visible to whoever used it!", &command)),
+        Some(false) => Ok(format!("🔧 Responses to **{}** will be visible to everyone!", &command)),
+        None => Ok(format!("🔧 Responses to **{}** will have their default visibility!", &command)),
+    }
+}
+
+
+pub async fn command_config_archive(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config archive");
+
+    let options = data.to_owned().option_hashmap();
+
+    let channel = options.opt_channel("channel")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to change the Archive Channel."))
+    }
+
+    match channel {
+        Some(channel) => {
+            if !transcripts_enabled() {
+                return Err(BobError::from_msg(ErrorKind::Host, "Transcripts are disabled on this instance of Bob."))
+            }
+
+            if channel.kind != ChannelType::Text {
+                return Err(BobError::from_msg(ErrorKind::User, "Only Text Channels are valid Archive Channels."))
+            }
+
+            guild_id.edit_archive_channel(Some(channel.id))?;
+
+            Ok(format!("🗄 Transcripts of the deleted channels will be sent to {}!", &channel.id.mention()))
+        },
+        None => {
+            guild_id.edit_archive_channel(None)?;
+
+            Ok("🗄 Transcripts of the deleted channels won't be sent anymore!".to_string())
+        },
+    }
+}
+
+
+pub async fn command_config_companion(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config companion");
+
+    let options = data.to_owned().option_hashmap();
+
+    let mode = options.opt_string("mode")?.map(|m| CompanionMode::parse(&m)).transpose()?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to change the companion of temporary channels."))
+    }
+
+    match mode {
+        Some(CompanionMode::Channel) => {
+            guild_id.set_companion_mode(CompanionMode::Channel)?;
+            Ok("🔧 A text channel will be built alongside each temporary channel!".to_string())
+        },
+        Some(CompanionMode::Thread) => {
+            guild_id.set_companion_mode(CompanionMode::Thread)?;
+            Ok("🔧 A thread in the command channel will be built alongside each temporary channel!".to_string())
+        },
+        None => {
+            guild_id.unset_companion_mode()?;
+            Ok("🔧 Nothing will be built alongside temporary channels anymore!".to_string())
+        },
+    }
+}
+
+
+/// How long before the start of an event its room is built, if not specified: 10 minutes.
+const DEFAULT_EVENT_LEAD_TIME: u64 = 600;
+
+
+pub async fn command_config_events(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config events");
+
+    let options = data.to_owned().option_hashmap();
+
+    let preset = options.opt_string("preset")?.map(|p| p.channelify());
+    let lead = options.opt_integer("lead")?.map_or(DEFAULT_EVENT_LEAD_TIME, |l| l.unsigned_abs());
+    let category = options.opt_channel("category")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_events() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Events** permission on the guild to change how event rooms are built."))
+    }
+
+    if let Some(category) = &category {
+        if category.kind != ChannelType::Category {
+            return Err(BobError::from_msg(ErrorKind::User, "Event rooms can only be built in categories."))
+        }
+    }
+
+    match preset {
+        Some(preset) => {
+            guild_id.get_preset(&preset)?
+                .bob_catch(ErrorKind::User, "No such preset.")?;
+
+            guild_id.set_event_room_config(preset.clone(), Duration::from_secs(lead), category.map(|c| c.id))?;
+            schedule_event_rooms(ctx, guild_id).await?;
+
+            Ok(format!("📅 Rooms will be built with preset `{}` **{} minutes** before the start of scheduled events!", &preset, lead / 60))
+        },
+        None => {
+            guild_id.unset_event_room_config()?;
+
+            Ok("📅 Rooms won't be built for scheduled events anymore!".to_string())
+        },
+    }
+}
+
+
+/// The largest file that can be imported, in bytes.
+const MAX_IMPORT_SIZE: u64 = 1_000_000;
+
+/// The maximum length of a message sent to Discord.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+
+/// Join the `header`, as many of the `lines` as possible and the `footer` into a message that fits in
+/// [MAX_MESSAGE_LENGTH], mentioning how many lines were left out.
+fn fit_lines(header: &str, lines: &[String], footer: &str) -> String {
+    let mut included: Vec<&str> = vec![header];
+    let mut length = header.chars().count() + footer.chars().count() + 50;
+
+    for (index, line) in lines.iter().enumerate() {
+        length += line.chars().count() + 1;
+        if length > MAX_MESSAGE_LENGTH {
+            let omitted = format!("_...and **{}** more._", lines.len() - index);
+            return format!("{}\n{}{}", included.join("\n"), omitted, footer);
+        }
+        included.push(line);
+    }
+
+    format!("{}{}", included.join("\n"), footer)
+}
+
+
+pub async fn command_config_doctor(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, _data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config doctor");
+
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to check the configuration."))
+    }
+
+    let guild = guild_id.ext_partial_guild(&ctx.http).await?;
+    let diagnostics = diagnose(ctx, &guild).await?;
+
+    let problems = diagnostics.iter().filter(|d| !d.ok).count();
+    let header = match problems {
+        0 => "🩺 Everything looks fine!".to_string(),
+        1 => "🩺 Found **1** problem:".to_string(),
+        n => format!("🩺 Found **{}** problems:", n),
+    };
+
+    // List the problems first, so that only passing checks are left out if the response is too long
+    let mut sorted: Vec<_> = diagnostics.iter().collect();
+    sorted.sort_by_key(|d| d.ok);
+
+    let mut lines: Vec<String> = vec![];
+    for diagnostic in sorted {
+        match &diagnostic.fix {
+            None => lines.push(format!("✅ {}", &diagnostic.description)),
+            Some(fix) => lines.push(format!("❌ {}\n    ↳ {}", &diagnostic.description, fix)),
+        }
+    }
+
+    Ok(fit_lines(&header, &lines, ""))
+}
+
+
+/// The exported file is sent in an ephemeral follow-up of the `interaction`, as it contains the bans and the presets of
+/// the guild.
+pub async fn command_config_export(ctx: &Context, guild_id: GuildId, interaction: &ApplicationCommandInteraction, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config export");
+
+    let options = data.to_owned().option_hashmap();
+
+    let format = match options.opt_string("format")?.as_deref() {
+        None | Some("json") => ExportFormat::Json,
+        Some("toml") => ExportFormat::Toml,
+        Some(_) => return Err(BobError::from_msg(ErrorKind::Developer, "Invalid export format.")),
+    };
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to export its configuration."))
+    }
+
+    let guild = guild_id.ext_partial_guild(&ctx.http).await?;
+    let export = GuildExport::collect(&ctx.http, &guild).await?;
+    let contents = export.serialize(format)?;
+
+    interaction.create_followup_message(&ctx.http, |m| m
+        .content(format!("📦 Configuration of **{}**:", &guild.name))
+        .add_file(AttachmentType::Bytes {
+            data: contents.into_bytes().into(),
+            filename: format!("bob-{}.{}", &guild_id, format.extension()),
+        })
+        .ephemeral(true)
+    ).await.bob_catch(ErrorKind::External, "Couldn't send the exported file.")?;
+
+    Ok(format!("📦 Exported the configuration and **{}** presets!", export.presets.len()))
+}
+
+
+pub async fn command_config_import(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config import");
+
+    let options = data.to_owned().option_hashmap();
+
+    let file = options.req_attachment("file")?;
+    let apply = options.opt_boolean("apply")?.unwrap_or(false);
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to import a configuration."))
+    }
+
+    let format = ExportFormat::from_filename(&file.filename)?;
+    if file.size > MAX_IMPORT_SIZE {
+        return Err(BobError::from_msg(ErrorKind::User, "The file is too large to be a configuration export."))
+    }
+
+    let contents = file.download()
+        .await.bob_catch(ErrorKind::External, "Couldn't download the attached file.")?;
+    let export = GuildExport::deserialize(&contents, format)?;
+
+    let guild = guild_id.ext_partial_guild(&ctx.http).await?;
+    let import = export.remap(&ctx.http, &guild).await?;
+    import.check_presets(guild_id, member)?;
+
+    let mut lines = import.diff(guild_id)?;
+    lines.extend(import.notes.iter().cloned());
+
+    if !apply {
+        return Ok(fit_lines(
+            "📋 The import would make the following changes:",
+            &lines,
+            "\n\nRun the command again with `apply: True` to apply them.",
+        ))
+    }
+
+    import.apply(guild_id)?;
+
+    Ok(fit_lines("📥 Configuration imported successfully!", &lines, ""))
+}
+
+
+pub async fn command_config_ban(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config ban");
+
+    let options = data.to_owned().option_hashmap();
+
+    let user = options.req_user("member")?;
+    let duration = options.opt_integer("duration")?;
+    let reason = options.opt_string("reason")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.moderate_members() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Moderate Members** permission on the guild to ban members from building channels."))
+    }
+
+    let expires_at = match duration {
+        Some(duration) => Some(SystemTime::now() + Duration::from_secs(
+            u64::try_from(duration).bob_catch(ErrorKind::User, "The duration can't be negative.")?
+        )),
+        None => None,
+    };
+    guild_id.set_build_ban(user.id, expires_at, reason.clone())?;
+
+    let until = match expires_at {
+        Some(expires_at) => format!(" until <t:{}:f>", expires_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()),
+        None => "".to_string(),
+    };
+    let reason = match reason {
+        Some(reason) => format!(" Reason: {}", reason),
+        None => "".to_string(),
+    };
+
+    if let Err(e) = log_moderation(ctx, guild_id, &format!(
+        "🛡 {} banned {} from building channels{}.{}", member.mention(), user.mention(), until, reason
+    )).await {
+        warn!("{}", &e);
+    }
+
+    Ok(format!("🛡 {} can't build channels anymore{}!", user.mention(), until))
+}
+
+
+pub async fn command_config_unban(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config unban");
+
+    let options = data.to_owned().option_hashmap();
+
+    let user = options.req_user("member")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.moderate_members() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Moderate Members** permission on the guild to unban members from building channels."))
+    }
+
+    guild_id.unset_build_ban(user.id)?;
+
+    if let Err(e) = log_moderation(ctx, guild_id, &format!(
+        "🛡 {} allowed {} to build channels again.", member.mention(), user.mention()
+    )).await {
+        warn!("{}", &e);
+    }
+
+    Ok(format!("🛡 {} can build channels again!", user.mention()))
+}
+
+
+pub async fn command_config_blocklist(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config blocklist");
+
+    let options = data.to_owned().option_hashmap();
+
+    let action = options.req_string("action")?;
+    let pattern = options.req_string("pattern")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to change the name blocklist."))
+    }
+
+    match action.as_str() {
+        "add" => {
+            compile_pattern(&pattern)?;
+            guild_id.set_blocked_name(&pattern, true)?;
+
+            Ok(format!("🛡 Channels whose name matches `{}` can't be built anymore!", &pattern))
+        },
+        "remove" => {
+            guild_id.set_blocked_name(&pattern, false)?;
+
+            Ok(format!("🛡 Channels whose name matches `{}` can be built again!", &pattern))
+        },
+        _ => Err(BobError::from_msg(ErrorKind::Developer, "Invalid blocklist action.")),
+    }
+}
+
+
+pub async fn command_config_modlog(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: config modlog");
+
+    let options = data.to_owned().option_hashmap();
+
+    let channel = options.opt_channel("channel")?;
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_guild() {
+        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Guild** permission on the guild to change the Moderation Channel."))
+    }
+
+    match channel {
+        Some(channel) => {
+            if channel.kind != ChannelType::Text {
+                return Err(BobError::from_msg(ErrorKind::User, "Only Text Channels are valid Moderation Channels."))
+            }
+
+            guild_id.edit_moderation_channel(Some(channel.id))?;
+
+            Ok(format!("🛡 Moderation actions will be logged in {}!", &channel.id.mention()))
+        },
+        None => {
+            guild_id.edit_moderation_channel(None)?;
+
+            Ok("🛡 Moderation actions won't be logged anymore!".to_string())
+        },
+    }
+}
@@ -0,0 +1,66 @@
+use serenity::prelude::*;
+use serenity::model::prelude::*;
+use serenity::model::application::interaction::application_command::{CommandData, ResolvedTarget};
+use crate::extensions::*;
+use crate::errors::*;
+use crate::database::models::{MayHaveBeenCreatedByBob};
+use crate::utils::permission_overwrites::{fetch_grantable_permissions, restrict};
+
+
+/// The name of the user command which invites a member to the channel of the caller.
+pub const INVITE_COMMAND_NAME: &str = "Invite to my room";
+
+
+pub async fn command_invite(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
+    debug!("Called command: invite");
+
+    let target = match data.target() {
+        Some(ResolvedTarget::User(user, _)) => user,
+        _ => return Err(BobError::from_msg(ErrorKind::Developer, "Interaction has no target user")),
+    };
+
+    if target.id == member.user.id {
+        return Err(BobError::from_msg(ErrorKind::User, "You can't invite yourself to your own channel."))
+    }
+
+    let voice_channel_id = ctx.cache
+        .guild_field(guild_id, |g| g.voice_states.get(&member.user.id).and_then(|vs| vs.channel_id))
+        .flatten()
+        .bob_catch(ErrorKind::User, "You're not connected to voice chat!")?;
+
+    let channel = voice_channel_id.ext_guild_channel(&ctx.http).await?;
+
+    if channel.get_owner()? != Some(member.user.id) {
+        return Err(BobError::from_msg(ErrorKind::User, "You can only invite members to a channel you built with Bob."))
+    }
+
+    let guild = guild_id.ext_partial_guild(&ctx.http).await?;
+    let grantable = fetch_grantable_permissions(ctx, &guild, channel.parent_id).await?;
+
+    let mut permows = [PermissionOverwrite {
+        allow: Permissions::VIEW_CHANNEL | Permissions::CONNECT,
+        deny: Permissions::empty(),
+        kind: PermissionOverwriteType::Member(target.id),
+    }];
+    let ungranted = restrict(&mut permows, grantable);
+    if !ungranted.is_empty() {
+        return Err(BobError::from_msg(ErrorKind::Admin, &format!(
+            "I can't invite members to {}, as I don't have these permissions myself: {}.",
+            &channel.mention(),
+            ungranted.get_permission_names().join(", ")
+        )))
+    }
+
+    channel.create_permission(&ctx.http, &permows[0])
+        .await.bob_catch(ErrorKind::Admin, "Couldn't edit the permissions of the channel.")?;
+
+    let link = format!("https://discord.com/channels/{}/{}", &guild_id, &channel.id);
+    let dm = target.direct_message(&ctx.http, |m| m
+        .content(format!("✉ {} invited you to join {}!\n{}", &member.mention(), &channel.mention(), &link))
+    ).await;
+
+    match dm {
+        Ok(_) => Ok(format!("✉ Invited {} to {}!", &target.mention(), &channel.mention())),
+        Err(_) => Ok(format!("✉ Invited {} to {}, but I couldn't send them a message about it.", &target.mention(), &channel.mention())),
+    }
+}
@@ -3,3 +3,5 @@
 pub mod build;
 pub mod config;
 pub mod save;
+pub mod preset;
+pub mod invite;
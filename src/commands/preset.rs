@@ -0,0 +1,175 @@
+use std::convert::{TryFrom};
+use serenity::prelude::*;
+use serenity::model::prelude::*;
+use serenity::model::application::interaction::application_command::CommandDataOption;
+use crate::extensions::*;
+use crate::errors::*;
+use crate::database::models::{Preset, CanGetPresetData, CanSavePresetData, WithPresetHistory};
+use crate::utils::channel_names::{Channelizable};
+
+
+/// Get the [Preset] with the name specified in the `preset` option.
+fn get_preset_option(guild_id: GuildId, data: &Vec<CommandDataOption>) -> BobResult<Preset> {
+    let options = data.to_owned().option_hashmap();
+    let name = options.req_string("preset")?.channelify();
+
+    guild_id.get_preset(&name)?
+        .bob_catch(ErrorKind::User, "No such preset.")
+}
+
+
+/// Ensure the [Member] has the **Manage Channels** permission on the guild.
+fn require_manage_channels(member: &Member, action: &str) -> BobResult<()> {
+    let permissions = member.permissions
+        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+    if !permissions.manage_channels() {
+        return Err(BobError::from_msg(ErrorKind::User, &format!("You need to have **Manage Channels** permission on the guild to {}.", action)))
+    }
+
+    Ok(())
+}
+
+
+pub async fn command_preset_info(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, _member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset info");
+
+    let preset = get_preset_option(guild_id, data)?;
+
+    let author = match preset.author()? {
+        Some(author) => author.mention().to_string(),
+        None => "_unknown_".to_string(),
+    };
+    let roles = match preset.allowed_roles()?.as_slice() {
+        [] => "everyone".to_string(),
+        roles => roles.iter().map(|r| r.mention().to_string()).collect::<Vec<String>>().join(", "),
+    };
+
+    Ok(format!(
+        "💿 Preset `{}`\nAuthor: {}\nLocked: {}\nUsable by: {}",
+        &preset.preset_name,
+        &author,
+        if preset.locked { "yes" } else { "no" },
+        &roles,
+    ))
+}
+
+
+pub async fn command_preset_delete(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset delete");
+
+    let preset = get_preset_option(guild_id, data)?;
+    preset.check_edit(member)?;
+
+    guild_id.delete_preset(&preset.preset_name)?;
+
+    Ok(format!("🗑 Preset `{}` deleted!", &preset.preset_name))
+}
+
+
+pub async fn command_preset_lock(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset lock");
+
+    let options = data.to_owned().option_hashmap();
+    let locked = options.req_boolean("locked")?;
+
+    let preset = get_preset_option(guild_id, data)?;
+    require_manage_channels(member, "lock or unlock a preset")?;
+
+    guild_id.set_preset_locked(&preset.preset_name, locked)?;
+
+    match locked {
+        true => Ok(format!("🔒 Preset `{}` locked!", &preset.preset_name)),
+        false => Ok(format!("🔓 Preset `{}` unlocked!", &preset.preset_name)),
+    }
+}
+
+
+pub async fn command_preset_restrict(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset restrict");
+
+    let options = data.to_owned().option_hashmap();
+    let role = options.req_role("role")?;
+
+    let preset = get_preset_option(guild_id, data)?;
+    require_manage_channels(member, "restrict a preset")?;
+
+    let mut roles = preset.allowed_roles()?;
+    if roles.contains(&role.id) {
+        return Err(BobError::from_msg(ErrorKind::User, "That role can already use the preset."))
+    }
+    roles.push(role.id);
+
+    guild_id.set_preset_allowed_roles(&preset.preset_name, &roles)?;
+
+    Ok(format!("🔐 Preset `{}` can now be used by {}!", &preset.preset_name, &role.mention()))
+}
+
+
+pub async fn command_preset_unrestrict(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset unrestrict");
+
+    let options = data.to_owned().option_hashmap();
+    let role = options.req_role("role")?;
+
+    let preset = get_preset_option(guild_id, data)?;
+    require_manage_channels(member, "restrict a preset")?;
+
+    let mut roles = preset.allowed_roles()?;
+    if !roles.contains(&role.id) {
+        return Err(BobError::from_msg(ErrorKind::User, "That role isn't in the list of the roles that can use the preset."))
+    }
+    roles.retain(|r| r != &role.id);
+
+    guild_id.set_preset_allowed_roles(&preset.preset_name, &roles)?;
+
+    match roles.is_empty() {
+        true => Ok(format!("🔓 Preset `{}` can now be used by everyone!", &preset.preset_name)),
+        false => Ok(format!("🔐 Preset `{}` can't be used by {} anymore!", &preset.preset_name, &role.mention())),
+    }
+}
+
+
+pub async fn command_preset_history(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, _member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset history");
+
+    let preset = get_preset_option(guild_id, data)?;
+    let revisions = guild_id.get_preset_revisions(&preset.preset_name)?;
+
+    let mut lines = vec![format!("📜 Revisions of preset `{}`:", &preset.preset_name)];
+    for (index, revision) in revisions.iter().enumerate() {
+        let author = match revision.author()? {
+            Some(author) => author.mention().to_string(),
+            None => "_unknown_".to_string(),
+        };
+
+        lines.push(format!(
+            "`#{}` saved <t:{}:R> by {}{}",
+            &revision.revision,
+            &revision.created_at,
+            &author,
+            if index == 0 { " (current)" } else { "" },
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+
+pub async fn command_preset_rollback(_ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &Vec<CommandDataOption>) -> BobResult<String> {
+    debug!("Called command: preset rollback");
+
+    let options = data.to_owned().option_hashmap();
+    let revision = i32::try_from(options.req_integer("revision")?)
+        .bob_catch(ErrorKind::User, "No such revision.")?;
+
+    let preset = get_preset_option(guild_id, data)?;
+    preset.check_edit(member)?;
+
+    let revision = guild_id.get_preset_revision(&preset.preset_name, revision)?
+        .bob_catch(ErrorKind::User, "No such revision.")?;
+
+    guild_id.save_preset_data(preset.preset_name.clone(), revision.data()?, true, Some(member.user.id))?;
+
+    Ok(format!("⏪ Preset `{}` rolled back to revision `#{}`!", &preset.preset_name, &revision.revision))
+}
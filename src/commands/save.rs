@@ -3,11 +3,11 @@ use serenity::model::prelude::*;
 use serenity::model::application::interaction::application_command::{CommandData};
 use crate::extensions::*;
 use crate::errors::*;
-use crate::database::models::{IntoPresetData, DatabaseAction};
+use crate::database::models::{IntoPresetData, CanGetPresetData, DatabaseAction};
 use crate::utils::channel_names::{Channelizable};
 
 
-pub async fn command_save(ctx: &Context, _guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
+pub async fn command_save(ctx: &Context, guild_id: GuildId, _channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
     debug!("Called command: save");
 
     let options = data.to_owned().options.option_hashmap();
@@ -15,14 +15,13 @@ pub async fn command_save(ctx: &Context, _guild_id: GuildId, _channel_id: Channe
     let template = options.req_channel("template")?.id.ext_guild_channel(&ctx.http).await?;
     let overwrite = options.opt_boolean("overwrite")?.unwrap_or(false);
 
-    let permissions = member.permissions
-        .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
-
-    if overwrite && !permissions.manage_channels() {
-        return Err(BobError::from_msg(ErrorKind::User, "You need to have **Manage Channels** permission on the guild to overwrite an existing preset."));
-    };
+    if overwrite {
+        if let Some(existing) = guild_id.get_preset(&preset)? {
+            existing.check_edit(member)?;
+        }
+    }
 
-    let action = template.save_as_preset(preset.clone(), overwrite)?;
+    let action = template.save_as_preset(&ctx.cache, preset.clone(), overwrite, member.user.id)?;
 
     match action {
         DatabaseAction::Created(_) => {
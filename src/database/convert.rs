@@ -2,8 +2,8 @@
 //! and [serenity].
 
 use std::convert::{TryFrom};
-use std::time::{Duration};
-use serenity::model::prelude::{ChannelId, GuildId};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serenity::model::prelude::{ChannelId, GuildId, UserId, RoleId, ScheduledEventId};
 use crate::errors::{BobResult, BobCatch, ErrorKind};
 
 
@@ -50,6 +50,63 @@ impl BobFrom<GuildId> for i64 {
 }
 
 
+impl BobFrom<i64> for UserId {
+    fn bobfrom(val: i64) -> BobResult<Self> {
+        let uid = u64::try_from(val)
+            .bob_catch(ErrorKind::Developer, "i64 couldn't be converted into a u64?!")?;
+
+        Ok(UserId(uid))
+    }
+}
+
+impl BobFrom<UserId> for i64 {
+    fn bobfrom(val: UserId) -> BobResult<Self> {
+        let uid = i64::try_from(val.0)
+            .bob_catch(ErrorKind::Developer, "UserId is larger than a i64")?;
+
+        Ok(uid)
+    }
+}
+
+
+impl BobFrom<i64> for RoleId {
+    fn bobfrom(val: i64) -> BobResult<Self> {
+        let rid = u64::try_from(val)
+            .bob_catch(ErrorKind::Developer, "i64 couldn't be converted into a u64?!")?;
+
+        Ok(RoleId(rid))
+    }
+}
+
+impl BobFrom<RoleId> for i64 {
+    fn bobfrom(val: RoleId) -> BobResult<Self> {
+        let rid = i64::try_from(val.0)
+            .bob_catch(ErrorKind::Developer, "RoleId is larger than a i64")?;
+
+        Ok(rid)
+    }
+}
+
+
+impl BobFrom<i64> for ScheduledEventId {
+    fn bobfrom(val: i64) -> BobResult<Self> {
+        let eid = u64::try_from(val)
+            .bob_catch(ErrorKind::Developer, "i64 couldn't be converted into a u64?!")?;
+
+        Ok(ScheduledEventId(eid))
+    }
+}
+
+impl BobFrom<ScheduledEventId> for i64 {
+    fn bobfrom(val: ScheduledEventId) -> BobResult<Self> {
+        let eid = i64::try_from(val.0)
+            .bob_catch(ErrorKind::Developer, "ScheduledEventId is larger than a i64")?;
+
+        Ok(eid)
+    }
+}
+
+
 impl BobFrom<i32> for Duration {
     fn bobfrom(val: i32) -> BobResult<Self> {
         let time = u64::try_from(val)
@@ -66,4 +123,25 @@ impl BobFrom<Duration> for i32 {
 
         Ok(time)
     }
-}
\ No newline at end of file
+}
+
+impl BobFrom<i64> for SystemTime {
+    fn bobfrom(val: i64) -> BobResult<Self> {
+        let time = u64::try_from(val)
+            .bob_catch(ErrorKind::Developer, "i64 couldn't be converted into a u64?!")?;
+
+        Ok(UNIX_EPOCH + Duration::from_secs(time))
+    }
+}
+
+impl BobFrom<SystemTime> for i64 {
+    fn bobfrom(val: SystemTime) -> BobResult<Self> {
+        let time = val.duration_since(UNIX_EPOCH)
+            .bob_catch(ErrorKind::Host, "System time is before the UNIX epoch.")?;
+
+        let time = i64::try_from(time.as_secs())
+            .bob_catch(ErrorKind::Developer, "SystemTime is larger than a i64")?;
+
+        Ok(time)
+    }
+}
@@ -1,14 +1,18 @@
 //! This module contains the database ORM models.
 
 use std::env::{var};
-use std::time::{Duration};
+use std::convert::{TryFrom};
+use std::time::{Duration, SystemTime};
+use std::collections::{BTreeSet};
+use std::num::{NonZeroU32};
 use diesel::prelude::*;
-use serenity::model::prelude::{ChannelId, GuildId, GuildChannel, ChannelType};
-use serenity::model::channel::{PermissionOverwrite};
+use serenity::model::prelude::{ChannelId, GuildId, GuildChannel, ChannelType, UserId, RoleId, PartialGuild, Permissions, Member, Mentionable, VideoQualityMode, ScheduledEventId};
+use serenity::model::channel::{PermissionOverwrite, PermissionOverwriteType};
+use serenity::cache::Cache;
 use serde::{Serialize, Deserialize};
 use serde_json;
 use crate::errors::{BobResult, BobCatch, ErrorKind, BobError};
-use crate::database::schema::{command_channels, deletion_times, channels_created, presets};
+use crate::database::schema::{command_channels, deletion_times, channels_created, presets, preset_revisions, default_presets, guild_departures, event_rooms, companion_modes, archive_channels, room_limits, cooldown_exemptions, overflow_categories, build_categories, category_orderings, command_visibilities, builder_roles, build_bans, blocked_names, moderation_channels};
 use crate::database::convert::{BobFrom};
 
 
@@ -268,6 +272,11 @@ impl WithDeletionTime for GuildId {
 pub struct CreatedChannel {
     pub guild_id: i64,
     pub channel_id: i64,
+    pub owner_id: Option<i64>,
+    /// The id of the scheduled event the channel was built for, if any.
+    pub event_id: Option<i64>,
+    /// The id of the text channel or thread built alongside the channel, if any.
+    pub companion_id: Option<i64>,
 }
 
 impl CreatedChannel {
@@ -300,7 +309,7 @@ impl CreatedChannel {
         }
     }
 
-    fn put_raw(gid: i64, cid: i64) -> BobResult<CreatedChannel> {
+    fn put_raw(gid: i64, cid: i64, oid: Option<i64>, eid: Option<i64>) -> BobResult<CreatedChannel> {
         use crate::database::schema::channels_created::dsl::*;
 
         match CreatedChannel::get_raw(gid, cid)? {
@@ -308,7 +317,7 @@ impl CreatedChannel {
                 Ok(v)
             },
             None => {
-                let cc = CreatedChannel {guild_id: gid, channel_id: cid};
+                let cc = CreatedChannel {guild_id: gid, channel_id: cid, owner_id: oid, event_id: eid, companion_id: None};
 
                 diesel::insert_into(channels_created)
                     .values(&cc)
@@ -317,11 +326,57 @@ impl CreatedChannel {
             }
         }
     }
+
+    fn get_by_event_raw(gid: i64, eid: i64) -> BobResult<Option<CreatedChannel>> {
+        use crate::database::schema::channels_created::dsl::*;
+
+        let mut results =
+            channels_created
+                .filter(guild_id.eq(gid).and(event_id.eq(eid)))
+                .limit(1)
+                .load::<CreatedChannel>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't retrieve Created Channels from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    fn delete_raw(gid: i64, cid: i64) -> BobResult<usize> {
+        use crate::database::schema::channels_created::dsl::*;
+
+        diesel::delete(channels_created.find((gid, cid)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete Created Channel from the database.")
+    }
+
+    fn set_companion_raw(gid: i64, cid: i64, compid: i64) -> BobResult<CreatedChannel> {
+        use crate::database::schema::channels_created::dsl::*;
+
+        diesel::update(channels_created.find((gid, cid)))
+            .set(companion_id.eq(Some(compid)))
+            .get_result::<CreatedChannel>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't edit Created Channel information in the database.")
+    }
 }
 
 pub trait MayHaveBeenCreatedByBob {
     fn was_created_by_bob(&self) -> BobResult<bool>;
-    fn mark_as_created_by_bob(&self) -> BobResult<CreatedChannel>;
+    /// Record that Bob created the channel for the given owner, and for the given scheduled event if it is its room.
+    fn mark_as_created_by_bob(&self, owner: UserId, event: Option<ScheduledEventId>) -> BobResult<CreatedChannel>;
+
+    /// Get the [UserId] of the member who asked Bob to create the channel, if it is known.
+    fn get_owner(&self) -> BobResult<Option<UserId>>;
+
+    /// Get the [ScheduledEventId] of the scheduled event the channel was built for, if any.
+    fn get_event(&self) -> BobResult<Option<ScheduledEventId>>;
+
+    /// Link the channel to the companion text channel or thread built alongside it.
+    fn mark_companion(&self, companion: ChannelId) -> BobResult<CreatedChannel>;
+
+    /// Get the [ChannelId] of the companion text channel or thread built alongside the channel, if any.
+    fn get_companion(&self) -> BobResult<Option<ChannelId>>;
 }
 
 impl MayHaveBeenCreatedByBob for GuildChannel {
@@ -332,9 +387,81 @@ impl MayHaveBeenCreatedByBob for GuildChannel {
         }
     }
 
-    fn mark_as_created_by_bob(&self) -> BobResult<CreatedChannel> {
-        debug!("Marking {} as created by Bob", &self.id);
-        CreatedChannel::put_raw(i64::bobfrom(self.guild_id)?, i64::bobfrom(self.id)?)
+    fn mark_as_created_by_bob(&self, owner: UserId, event: Option<ScheduledEventId>) -> BobResult<CreatedChannel> {
+        debug!("Marking {} as created by Bob for {} and event {:?}", &self.id, &owner, &event);
+        CreatedChannel::put_raw(
+            i64::bobfrom(self.guild_id)?,
+            i64::bobfrom(self.id)?,
+            Some(i64::bobfrom(owner)?),
+            event.map(i64::bobfrom).transpose()?,
+        )
+    }
+
+    fn get_owner(&self) -> BobResult<Option<UserId>> {
+        match CreatedChannel::get_raw(i64::bobfrom(self.guild_id)?, i64::bobfrom(self.id)?)? {
+            Some(CreatedChannel { owner_id: Some(oid), .. }) => Ok(Some(UserId::bobfrom(oid)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_event(&self) -> BobResult<Option<ScheduledEventId>> {
+        match CreatedChannel::get_raw(i64::bobfrom(self.guild_id)?, i64::bobfrom(self.id)?)? {
+            Some(CreatedChannel { event_id: Some(eid), .. }) => Ok(Some(ScheduledEventId::bobfrom(eid)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn mark_companion(&self, companion: ChannelId) -> BobResult<CreatedChannel> {
+        debug!("Marking {} as the companion of {}", &companion, &self.id);
+        CreatedChannel::set_companion_raw(i64::bobfrom(self.guild_id)?, i64::bobfrom(self.id)?, i64::bobfrom(companion)?)
+    }
+
+    fn get_companion(&self) -> BobResult<Option<ChannelId>> {
+        match CreatedChannel::get_raw(i64::bobfrom(self.guild_id)?, i64::bobfrom(self.id)?)? {
+            Some(CreatedChannel { companion_id: Some(compid), .. }) => Ok(Some(ChannelId::bobfrom(compid)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+pub trait WithCreatedChannels {
+    /// Get the [ChannelId]s of all the channels Bob created in the [GuildId], along with their owner, if known.
+    ///
+    /// Channels which were deleted without Bob noticing may be included.
+    fn get_created_channels(&self) -> BobResult<Vec<(ChannelId, Option<UserId>)>>;
+
+    /// Get the [ChannelId]s of the rooms Bob created in the [GuildId], along with their owner, if known, leaving out
+    /// the companion text channels and threads built alongside them.
+    ///
+    /// Channels which were deleted without Bob noticing may be included.
+    fn get_created_rooms(&self) -> BobResult<Vec<(ChannelId, Option<UserId>)>>;
+
+    /// Forget that Bob created the given channel, usually because it was deleted.
+    fn forget_created_channel(&self, cid: ChannelId) -> BobResult<()>;
+}
+
+impl WithCreatedChannels for GuildId {
+    fn get_created_channels(&self) -> BobResult<Vec<(ChannelId, Option<UserId>)>> {
+        CreatedChannel::get_all_raw(i64::bobfrom(*self)?)?
+            .into_iter()
+            .map(|cc| Ok((ChannelId::bobfrom(cc.channel_id)?, cc.owner_id.map(UserId::bobfrom).transpose()?)))
+            .collect()
+    }
+
+    fn get_created_rooms(&self) -> BobResult<Vec<(ChannelId, Option<UserId>)>> {
+        let created = CreatedChannel::get_all_raw(i64::bobfrom(*self)?)?;
+        let companions: BTreeSet<i64> = created.iter().filter_map(|cc| cc.companion_id).collect();
+
+        created
+            .into_iter()
+            .filter(|cc| !companions.contains(&cc.channel_id))
+            .map(|cc| Ok((ChannelId::bobfrom(cc.channel_id)?, cc.owner_id.map(UserId::bobfrom).transpose()?)))
+            .collect()
+    }
+
+    fn forget_created_channel(&self, cid: ChannelId) -> BobResult<()> {
+        CreatedChannel::delete_raw(i64::bobfrom(*self)?, i64::bobfrom(cid)?)?;
+        Ok(())
     }
 }
 
@@ -345,6 +472,9 @@ pub struct Preset {
     pub guild_id: i64,
     pub preset_name: String,
     pub preset_data: serde_json::Value,
+    pub author_id: Option<i64>,
+    pub locked: bool,
+    pub allowed_roles: Vec<i64>,
 }
 
 
@@ -374,7 +504,7 @@ impl Preset {
         }
     }
 
-    fn save_raw(gid: i64, name: String, data: PresetData, overwrite: bool) -> BobResult<DatabaseAction<Preset>> {
+    fn save_raw(gid: i64, name: String, data: PresetData, overwrite: bool, author: Option<i64>) -> BobResult<DatabaseAction<Preset>> {
         use crate::database::schema::presets::dsl::*;
 
         let data = serde_json::to_value::<PresetData>(data)
@@ -387,10 +517,12 @@ impl Preset {
                 }
 
                 let result = diesel::update(presets.find((pr.guild_id, pr.preset_name)))
-                    .set(preset_data.eq(data))
+                    .set(preset_data.eq(&data))
                     .get_result::<Preset>(&connect())
                     .bob_catch(ErrorKind::External, "Couldn't edit Preset in the database.")?;
 
+                PresetRevision::put_raw(gid, &result.preset_name, data, author)?;
+
                 Ok(DatabaseAction::Updated(result))
             },
             None => {
@@ -398,6 +530,9 @@ impl Preset {
                     guild_id: gid,
                     preset_name: name,
                     preset_data: data,
+                    author_id: author,
+                    locked: false,
+                    allowed_roles: vec![],
                 };
 
                 diesel::insert_into(presets)
@@ -405,62 +540,2578 @@ impl Preset {
                     .get_result::<Preset>(&connect())
                     .bob_catch(ErrorKind::External, "Couldn't add a new Preset into the database.")?;
 
+                PresetRevision::put_raw(gid, &cc.preset_name, cc.preset_data.clone(), author)?;
+
                 Ok(DatabaseAction::Created(cc))
             }
         }
     }
+
+    fn delete_raw(gid: i64, name: &str) -> BobResult<DatabaseAction<Preset>> {
+        use crate::database::schema::presets::dsl::*;
+
+        match Preset::get_raw(gid, name)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(pr) => {
+                PresetRevision::delete_all_raw(gid, name)?;
+                DefaultPreset::unset_all_raw(gid, name)?;
+                EventRoomConfig::unset_all_raw(gid, name)?;
+                diesel::delete(presets.find((pr.guild_id, pr.preset_name))).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Preset from the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+
+    fn set_locked_raw(gid: i64, name: &str, value: bool) -> BobResult<DatabaseAction<Preset>> {
+        use crate::database::schema::presets::dsl::*;
+
+        match Preset::get_raw(gid, name)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(pr) => {
+                let result = diesel::update(presets.find((pr.guild_id, pr.preset_name)))
+                    .set(locked.eq(value))
+                    .get_result::<Preset>(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't edit Preset in the database.")?;
+
+                Ok(DatabaseAction::Updated(result))
+            },
+        }
+    }
+
+    fn set_allowed_roles_raw(gid: i64, name: &str, value: Vec<i64>) -> BobResult<DatabaseAction<Preset>> {
+        use crate::database::schema::presets::dsl::*;
+
+        match Preset::get_raw(gid, name)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(pr) => {
+                let result = diesel::update(presets.find((pr.guild_id, pr.preset_name)))
+                    .set(allowed_roles.eq(value))
+                    .get_result::<Preset>(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't edit Preset in the database.")?;
+
+                Ok(DatabaseAction::Updated(result))
+            },
+        }
+    }
+
+    /// Deserialize the [PresetData] of the preset.
+    pub fn data(&self) -> BobResult<PresetData> {
+        serde_json::from_value::<PresetData>(self.preset_data.clone())
+            .bob_catch(ErrorKind::Developer, "Couldn't deserialize PresetData")
+    }
+
+    /// Get the [UserId] of the member who created the preset, if it is known.
+    pub fn author(&self) -> BobResult<Option<UserId>> {
+        match self.author_id {
+            None => Ok(None),
+            Some(aid) => Ok(Some(UserId::bobfrom(aid)?)),
+        }
+    }
+
+    /// Get the [RoleId]s allowed to use the preset; if empty, everyone is allowed to use it.
+    pub fn allowed_roles(&self) -> BobResult<Vec<RoleId>> {
+        self.allowed_roles
+            .iter()
+            .map(|rid| RoleId::bobfrom(*rid))
+            .collect()
+    }
+
+    /// Check whether the given [Member] is allowed to change or delete the preset.
+    ///
+    /// Members with the **Manage Channels** permission can always change presets, while the author of a preset can
+    /// change it only if it isn't locked.
+    pub fn check_edit(&self, member: &Member) -> BobResult<()> {
+        let permissions = member.permissions
+            .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+        if permissions.manage_channels() {
+            return Ok(())
+        }
+
+        if self.locked {
+            return Err(BobError::from_msg(ErrorKind::User, &format!(
+                "Preset `{}` is locked: only members with **Manage Channels** permission on the guild can change it.",
+                &self.preset_name
+            )))
+        }
+
+        if self.author()? != Some(member.user.id) {
+            return Err(BobError::from_msg(ErrorKind::User, &format!(
+                "Only the author of preset `{}` or members with **Manage Channels** permission on the guild can change it.",
+                &self.preset_name
+            )))
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the given [Member] is allowed to build channels with the preset.
+    pub fn check_use(&self, member: &Member) -> BobResult<()> {
+        let allowed = self.allowed_roles()?;
+
+        if allowed.is_empty() || member.roles.iter().any(|r| allowed.contains(r)) {
+            return Ok(())
+        }
+
+        let roles: Vec<String> = allowed.iter().map(|r| r.mention().to_string()).collect();
+
+        Err(BobError::from_msg(ErrorKind::User, &format!(
+            "You need one of these roles to use preset `{}`: {}",
+            &self.preset_name,
+            roles.join(", ")
+        )))
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The settings of a channel stored in a [Preset].
+///
+/// Fields added after the first version of the format have a default value, so that older presets can still be
+/// deserialized.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PresetData {
+    /// The version of the format the preset was saved with.
+    #[serde(default = "PresetData::legacy_version")]
+    pub version: u32,
     pub bitrate: u64,
     pub user_limit: Option<u64>,
-    pub permissions: Vec<PermissionOverwrite>
+    /// Legacy overwrites with an unknown kind of target are dropped.
+    #[serde(deserialize_with = "PresetPermission::deserialize_all")]
+    pub permissions: Vec<PresetPermission>,
+    /// _Since version 2._ Either [ChannelType::Voice] or [ChannelType::Stage].
+    #[serde(default)]
+    pub kind: Option<ChannelType>,
+    /// _Since version 2._
+    #[serde(default)]
+    pub video_quality: Option<VideoQualityMode>,
+    /// _Since version 2._ [None] lets Discord choose the region automatically.
+    #[serde(default)]
+    pub rtc_region: Option<String>,
+    /// _Since version 2._
+    #[serde(default)]
+    pub nsfw: bool,
+    /// _Since version 2._
+    #[serde(default)]
+    pub topic: Option<String>,
+}
+
+impl PresetData {
+    /// The current version of the [PresetData] format.
+    pub const VERSION: u32 = 2;
+
+    /// The version of the presets saved before the format was versioned.
+    fn legacy_version() -> u32 {
+        1
+    }
+
+    /// Resolve the [PresetPermission]s of the preset into [PermissionOverwrite]s for the given [PartialGuild] and
+    /// creator.
+    ///
+    /// Permissions targeting roles that don't exist in the guild are skipped.
+    pub fn permission_overwrites(&self, guild: &PartialGuild, creator_id: UserId) -> Vec<PermissionOverwrite> {
+        self.permissions
+            .iter()
+            .filter_map(|p| p.resolve(guild, creator_id))
+            .collect()
+    }
+}
+
+/// The target of a [PresetPermission], stored in a way that doesn't depend on the guild the preset was saved in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PresetTarget {
+    /// The `@everyone` role of the guild.
+    Everyone,
+    /// The member who is building the channel.
+    Creator,
+    /// The role with the given name.
+    Role { name: String },
+    /// The role with the given id, as stored by presets saved by older versions of the bot.
+    RoleId { id: u64 },
+    /// A specific member.
+    Member { id: u64 },
+}
+
+/// A [PermissionOverwrite] whose target is a [PresetTarget].
+#[derive(Serialize, Debug, Clone)]
+pub struct PresetPermission {
+    pub target: PresetTarget,
+    pub allow: Permissions,
+    pub deny: Permissions,
+}
+
+/// The formats a [PresetPermission] may be stored in.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PresetPermissionRepr {
+    Symbolic {
+        target: PresetTarget,
+        allow: Permissions,
+        deny: Permissions,
+    },
+    Legacy(PermissionOverwrite),
+}
+
+impl PresetPermissionRepr {
+    /// Convert the stored format into a [PresetPermission], if its target is of a known kind.
+    fn into_permission(self) -> Option<PresetPermission> {
+        match self {
+            PresetPermissionRepr::Symbolic { target, allow, deny } => {
+                Some(PresetPermission { target, allow, deny })
+            },
+            PresetPermissionRepr::Legacy(permow) => {
+                let target = match permow.kind {
+                    PermissionOverwriteType::Member(uid) => PresetTarget::Member { id: uid.0 },
+                    PermissionOverwriteType::Role(rid) => PresetTarget::RoleId { id: rid.0 },
+                    kind => {
+                        warn!("Dropping legacy preset overwrite with unknown target {:?}", &kind);
+                        return None
+                    },
+                };
+
+                Some(PresetPermission { target, allow: permow.allow, deny: permow.deny })
+            },
+        }
+    }
+}
+
+impl PresetPermission {
+    /// Deserialize a list of [PresetPermission]s in any of the formats they may be stored in, dropping the ones with an
+    /// unknown kind of target.
+    fn deserialize_all<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<PresetPermission>, D::Error> {
+        let reprs = Vec::<PresetPermissionRepr>::deserialize(deserializer)?;
+        Ok(reprs.into_iter().filter_map(PresetPermissionRepr::into_permission).collect())
+    }
+
+    /// Convert the [PresetPermission] into a [PermissionOverwrite] valid in the given [PartialGuild], if its target
+    /// exists there.
+    pub fn resolve(&self, guild: &PartialGuild, creator_id: UserId) -> Option<PermissionOverwrite> {
+        let kind = match &self.target {
+            PresetTarget::Everyone => PermissionOverwriteType::Role(RoleId(guild.id.0)),
+            PresetTarget::Creator => PermissionOverwriteType::Member(creator_id),
+            PresetTarget::Role { name } => PermissionOverwriteType::Role(guild.role_by_name(name)?.id),
+            PresetTarget::RoleId { id } => PermissionOverwriteType::Role(guild.roles.get(&RoleId(*id))?.id),
+            PresetTarget::Member { id } => PermissionOverwriteType::Member(UserId(*id)),
+        };
+
+        Some(PermissionOverwrite { allow: self.allow, deny: self.deny, kind })
+    }
 }
 
 pub trait CanGetPresetData {
-    fn get_preset_data(&self, name: &str) -> BobResult<Option<PresetData>>;
+    fn get_preset(&self, name: &str) -> BobResult<Option<Preset>>;
+    fn get_all_preset_data(&self) -> BobResult<Vec<(String, PresetData)>>;
+    fn get_all_presets(&self) -> BobResult<Vec<Preset>>;
 }
 
 impl CanGetPresetData for GuildId {
-    fn get_preset_data(&self, name: &str) -> BobResult<Option<PresetData>> {
-        let preset = Preset::get_raw(self.0 as i64, name)?;
-        match preset {
-            Some (preset) => Ok(Some(
-                serde_json::from_value::<PresetData>(preset.preset_data)
-                    .bob_catch(ErrorKind::Developer, "Couldn't deserialize PresetData")?
-            )),
-            None => Ok(None),
-        }
+    fn get_preset(&self, name: &str) -> BobResult<Option<Preset>> {
+        Preset::get_raw(i64::bobfrom(*self)?, name)
+    }
+
+    fn get_all_preset_data(&self) -> BobResult<Vec<(String, PresetData)>> {
+        let presets = Preset::get_all_raw(i64::bobfrom(*self)?)?;
+
+        presets
+            .into_iter()
+            .map(|preset| Ok((preset.preset_name.clone(), preset.data()?)))
+            .collect()
+    }
+
+    fn get_all_presets(&self) -> BobResult<Vec<Preset>> {
+        Preset::get_all_raw(i64::bobfrom(*self)?)
+    }
+}
+
+pub trait CanSavePresetData {
+    fn save_preset_data(&self, name: String, data: PresetData, overwrite: bool, author: Option<UserId>) -> BobResult<DatabaseAction<Preset>>;
+    fn delete_preset(&self, name: &str) -> BobResult<DatabaseAction<Preset>>;
+    fn set_preset_locked(&self, name: &str, locked: bool) -> BobResult<DatabaseAction<Preset>>;
+    fn set_preset_allowed_roles(&self, name: &str, roles: &[RoleId]) -> BobResult<DatabaseAction<Preset>>;
+}
+
+impl CanSavePresetData for GuildId {
+    fn save_preset_data(&self, name: String, data: PresetData, overwrite: bool, author: Option<UserId>) -> BobResult<DatabaseAction<Preset>> {
+        let author = match author {
+            None => None,
+            Some(author) => Some(i64::bobfrom(author)?),
+        };
+
+        Preset::save_raw(i64::bobfrom(*self)?, name, data, overwrite, author)
+    }
+
+    fn delete_preset(&self, name: &str) -> BobResult<DatabaseAction<Preset>> {
+        Preset::delete_raw(i64::bobfrom(*self)?, name)
+    }
+
+    fn set_preset_locked(&self, name: &str, locked: bool) -> BobResult<DatabaseAction<Preset>> {
+        Preset::set_locked_raw(i64::bobfrom(*self)?, name, locked)
+    }
+
+    fn set_preset_allowed_roles(&self, name: &str, roles: &[RoleId]) -> BobResult<DatabaseAction<Preset>> {
+        let roles = roles
+            .iter()
+            .map(|r| i64::bobfrom(*r))
+            .collect::<BobResult<Vec<i64>>>()?;
+
+        Preset::set_allowed_roles_raw(i64::bobfrom(*self)?, name, roles)
     }
 }
 
 pub trait IntoPresetData {
-    fn preset_data(self) -> BobResult<PresetData>;
-    fn save_as_preset(&self, name: String, overwrite: bool) -> BobResult<DatabaseAction<Preset>>;
+    /// Capture the settings of the channel into a [PresetData].
+    ///
+    /// The [PermissionOverwrite]s of the channel are converted into [PresetPermission]s, dropping the ones of Bob and
+    /// of the owner of the channel, which are recreated every time a channel is built.
+    fn preset_data(self, cache: &Cache) -> BobResult<PresetData>;
+    fn save_as_preset(&self, cache: &Cache, name: String, overwrite: bool, author: UserId) -> BobResult<DatabaseAction<Preset>>;
 }
 
 impl IntoPresetData for GuildChannel {
-    fn preset_data(self) -> BobResult<PresetData> {
-        if self.kind != ChannelType::Voice {
-            return Err(BobError::from_msg(ErrorKind::User, "Channel is not a voice channel"))
+    fn preset_data(self, cache: &Cache) -> BobResult<PresetData> {
+        if self.kind != ChannelType::Voice && self.kind != ChannelType::Stage {
+            return Err(BobError::from_msg(ErrorKind::User, "Channel is not a voice or stage channel"))
         }
 
+        let own_id = cache.current_user().id;
+        let owner_id = self.get_owner()?;
+
+        let permissions = self.permission_overwrites
+            .iter()
+            .filter_map(|permow| {
+                let target = match permow.kind {
+                    PermissionOverwriteType::Member(uid) if uid == own_id || Some(uid) == owner_id => {
+                        return None
+                    },
+                    PermissionOverwriteType::Member(uid) => {
+                        PresetTarget::Member { id: uid.0 }
+                    },
+                    PermissionOverwriteType::Role(rid) if rid.0 == self.guild_id.0 => {
+                        PresetTarget::Everyone
+                    },
+                    PermissionOverwriteType::Role(rid) => match cache.role(self.guild_id, rid) {
+                        Some(role) => PresetTarget::Role { name: role.name },
+                        None => PresetTarget::RoleId { id: rid.0 },
+                    },
+                    _ => return None,
+                };
+
+                Some(PresetPermission { target, allow: permow.allow, deny: permow.deny })
+            })
+            .collect();
+
         Ok(
             PresetData {
+                version: PresetData::VERSION,
                 bitrate: self.bitrate.bob_catch(ErrorKind::External, "Voice Channel has no bitrate")?,
                 user_limit: self.user_limit,
-                permissions: self.permission_overwrites
+                permissions,
+                kind: Some(self.kind),
+                video_quality: self.video_quality_mode,
+                rtc_region: self.rtc_region,
+                nsfw: self.nsfw,
+                topic: self.topic,
             }
         )
     }
 
-    fn save_as_preset(&self, name: String, overwrite: bool) -> BobResult<DatabaseAction<Preset>> {
+    fn save_as_preset(&self, cache: &Cache, name: String, overwrite: bool, author: UserId) -> BobResult<DatabaseAction<Preset>> {
         Preset::save_raw(
-            i64::from(self.guild_id),
+            i64::bobfrom(self.guild_id)?,
             name,
-            self.to_owned().preset_data()?,
-            overwrite
+            self.to_owned().preset_data(cache)?,
+            overwrite,
+            Some(i64::bobfrom(author)?)
         )
     }
 }
+
+
+
+/// The number of revisions kept for each preset if `BOB_PRESET_HISTORY_SIZE` is not set.
+const DEFAULT_PRESET_HISTORY_SIZE: i32 = 10;
+
+/// Get the number of revisions to keep for each preset, reading it from the `BOB_PRESET_HISTORY_SIZE` environment
+/// variable.
+fn preset_history_size() -> BobResult<i32> {
+    match var("BOB_PRESET_HISTORY_SIZE") {
+        Err(_) => Ok(DEFAULT_PRESET_HISTORY_SIZE),
+        Ok(v) => {
+            let size = v.parse::<NonZeroU32>()
+                .bob_catch(ErrorKind::Host, "BOB_PRESET_HISTORY_SIZE must be an integer of at least 1")?;
+
+            i32::try_from(size.get())
+                .bob_catch(ErrorKind::Host, "BOB_PRESET_HISTORY_SIZE is too large")
+        },
+    }
+}
+
+
+#[derive(Queryable, Insertable)]
+#[table_name="preset_revisions"]
+pub struct PresetRevision {
+    pub guild_id: i64,
+    pub preset_name: String,
+    pub revision: i32,
+    pub preset_data: serde_json::Value,
+    pub author_id: Option<i64>,
+    pub created_at: i64,
+}
+
+impl PresetRevision {
+    /// Get all the raw [PresetRevision] structs of a preset, from the most recent to the oldest.
+    fn get_all_raw(gid: i64, name: &str) -> BobResult<Vec<PresetRevision>> {
+        use crate::database::schema::preset_revisions::dsl::*;
+
+        preset_revisions
+            .filter(guild_id.eq(gid).and(preset_name.eq(name)))
+            .order(revision.desc())
+            .load::<PresetRevision>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Preset Revisions from the database.")
+    }
+
+    fn get_raw(gid: i64, name: &str, rev: i32) -> BobResult<Option<PresetRevision>> {
+        use crate::database::schema::preset_revisions::dsl::*;
+
+        let mut results =
+            preset_revisions
+                .filter(guild_id.eq(gid).and(preset_name.eq(name)).and(revision.eq(rev)))
+                .limit(1)
+                .load::<PresetRevision>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't retrieve Preset Revisions from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Store a new revision of a preset, then delete the oldest ones exceeding the history size.
+    fn put_raw(gid: i64, name: &str, data: serde_json::Value, author: Option<i64>) -> BobResult<PresetRevision> {
+        use crate::database::schema::preset_revisions::dsl::*;
+
+        let latest = PresetRevision::get_all_raw(gid, name)?
+            .first()
+            .map_or(0, |r| r.revision);
+
+        let pr = PresetRevision {
+            guild_id: gid,
+            preset_name: name.to_string(),
+            revision: latest + 1,
+            preset_data: data,
+            author_id: author,
+            created_at: i64::bobfrom(SystemTime::now())?,
+        };
+
+        let result = diesel::insert_into(preset_revisions)
+            .values(&pr)
+            .get_result::<PresetRevision>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't add a new Preset Revision into the database.")?;
+
+        diesel::delete(
+            preset_revisions.filter(
+                guild_id.eq(gid)
+                    .and(preset_name.eq(name))
+                    .and(revision.le(result.revision - preset_history_size()?))
+            )
+        ).execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't prune old Preset Revisions from the database.")?;
+
+        Ok(result)
+    }
+
+    fn delete_all_raw(gid: i64, name: &str) -> BobResult<usize> {
+        use crate::database::schema::preset_revisions::dsl::*;
+
+        diesel::delete(preset_revisions.filter(guild_id.eq(gid).and(preset_name.eq(name))))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete Preset Revisions from the database.")
+    }
+
+    /// Deserialize the [PresetData] of the revision.
+    pub fn data(&self) -> BobResult<PresetData> {
+        serde_json::from_value::<PresetData>(self.preset_data.clone())
+            .bob_catch(ErrorKind::Developer, "Couldn't deserialize PresetData")
+    }
+
+    /// Get the [UserId] of the member who saved the revision, if it is known.
+    pub fn author(&self) -> BobResult<Option<UserId>> {
+        match self.author_id {
+            None => Ok(None),
+            Some(aid) => Ok(Some(UserId::bobfrom(aid)?)),
+        }
+    }
+}
+
+pub trait WithPresetHistory {
+    /// Get all the stored revisions of a preset, from the most recent to the oldest.
+    fn get_preset_revisions(&self, name: &str) -> BobResult<Vec<PresetRevision>>;
+
+    /// Get a specific revision of a preset.
+    fn get_preset_revision(&self, name: &str, revision: i32) -> BobResult<Option<PresetRevision>>;
+}
+
+impl WithPresetHistory for GuildId {
+    fn get_preset_revisions(&self, name: &str) -> BobResult<Vec<PresetRevision>> {
+        PresetRevision::get_all_raw(i64::bobfrom(*self)?, name)
+    }
+
+    fn get_preset_revision(&self, name: &str, revision: i32) -> BobResult<Option<PresetRevision>> {
+        PresetRevision::get_raw(i64::bobfrom(*self)?, name, revision)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="default_presets"]
+pub struct DefaultPreset {
+    pub guild_id: i64,
+    /// Either the id of a category, or the id of the guild for the guild-wide default.
+    pub scope_id: i64,
+    pub preset_name: String,
+}
+
+impl DefaultPreset {
+    /// Get the raw [DefaultPreset] struct for the given guild id and scope id.
+    fn get_raw(gid: i64, sid: i64) -> BobResult<Option<DefaultPreset>> {
+        use crate::database::schema::default_presets::dsl::*;
+
+        let mut results: Vec<DefaultPreset> = default_presets
+            .filter(guild_id.eq(gid).and(scope_id.eq(sid)))
+            .limit(1)
+            .load::<DefaultPreset>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Default Preset information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [DefaultPreset] struct for the given guild id and scope id.
+    fn set_raw(gid: i64, sid: i64, name: String) -> BobResult<DatabaseAction<DefaultPreset>> {
+        use crate::database::schema::default_presets::dsl::*;
+
+        if let Some(dp) = DefaultPreset::get_raw(gid, sid)? {
+            let result = diesel::update(default_presets.find((dp.guild_id, dp.scope_id)))
+                .set(preset_name.eq(name))
+                .get_result::<DefaultPreset>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Default Preset information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let dp = DefaultPreset {
+                guild_id: gid,
+                scope_id: sid,
+                preset_name: name,
+            };
+
+            let result = diesel::insert_into(default_presets)
+                .values(&dp)
+                .get_result::<DefaultPreset>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Default Preset information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [DefaultPreset] struct for the given guild id and scope id.
+    fn unset_raw(gid: i64, sid: i64) -> BobResult<DatabaseAction<DefaultPreset>> {
+        use crate::database::schema::default_presets::dsl::*;
+
+        match DefaultPreset::get_raw(gid, sid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(dp) => {
+                diesel::delete(default_presets.find((dp.guild_id, dp.scope_id))).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Default Preset information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+
+    /// Unset all the raw [DefaultPreset] structs referring to the given preset.
+    fn unset_all_raw(gid: i64, name: &str) -> BobResult<usize> {
+        use crate::database::schema::default_presets::dsl::*;
+
+        diesel::delete(default_presets.filter(guild_id.eq(gid).and(preset_name.eq(name))))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete Default Preset information in the database.")
+    }
+}
+
+pub trait WithDefaultPreset {
+    /// Get the name of the default preset of the given category, or of the whole [GuildId] if `category` is [None].
+    fn get_default_preset(&self, category: Option<ChannelId>) -> BobResult<Option<String>>;
+
+    /// Set the default preset of the given category, or of the whole [GuildId] if `category` is [None].
+    fn set_default_preset(&self, category: Option<ChannelId>, name: String) -> BobResult<DatabaseAction<DefaultPreset>>;
+
+    /// Unset the default preset of the given category, or of the whole [GuildId] if `category` is [None].
+    fn unset_default_preset(&self, category: Option<ChannelId>) -> BobResult<DatabaseAction<DefaultPreset>>;
+
+    /// Find the default preset to use for a channel built in the given category, looking first at the category and
+    /// then at the whole [GuildId].
+    fn find_default_preset(&self, category: Option<ChannelId>) -> BobResult<Option<String>>;
+}
+
+/// Get the scope id of a [DefaultPreset] for the given guild and optional category.
+fn default_preset_scope(gid: GuildId, category: Option<ChannelId>) -> BobResult<i64> {
+    match category {
+        Some(cid) => i64::bobfrom(cid),
+        None => i64::bobfrom(gid),
+    }
+}
+
+impl WithDefaultPreset for GuildId {
+    fn get_default_preset(&self, category: Option<ChannelId>) -> BobResult<Option<String>> {
+        let gid = i64::bobfrom(*self)?;
+        let sid = default_preset_scope(*self, category)?;
+
+        Ok(DefaultPreset::get_raw(gid, sid)?.map(|dp| dp.preset_name))
+    }
+
+    fn set_default_preset(&self, category: Option<ChannelId>, name: String) -> BobResult<DatabaseAction<DefaultPreset>> {
+        let gid = i64::bobfrom(*self)?;
+        let sid = default_preset_scope(*self, category)?;
+
+        DefaultPreset::set_raw(gid, sid, name)
+    }
+
+    fn unset_default_preset(&self, category: Option<ChannelId>) -> BobResult<DatabaseAction<DefaultPreset>> {
+        let gid = i64::bobfrom(*self)?;
+        let sid = default_preset_scope(*self, category)?;
+
+        DefaultPreset::unset_raw(gid, sid)
+    }
+
+    fn find_default_preset(&self, category: Option<ChannelId>) -> BobResult<Option<String>> {
+        if category.is_some() {
+            if let Some(name) = self.get_default_preset(category)? {
+                return Ok(Some(name))
+            }
+        }
+
+        self.get_default_preset(None)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="event_rooms"]
+pub struct EventRoomConfig {
+    pub guild_id: i64,
+    pub preset_name: String,
+    /// How many seconds before the start of an event its room should be built.
+    pub lead_time: i32,
+    pub category_id: Option<i64>,
+}
+
+impl EventRoomConfig {
+    /// Get the raw [EventRoomConfig] struct for the given guild id.
+    fn get_raw(gid: i64) -> BobResult<Option<EventRoomConfig>> {
+        use crate::database::schema::event_rooms::dsl::*;
+
+        let mut results: Vec<EventRoomConfig> = event_rooms
+            .filter(guild_id.eq(gid))
+            .limit(1)
+            .load::<EventRoomConfig>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Event Room information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [EventRoomConfig] struct for the given guild id.
+    fn set_raw(gid: i64, name: String, time: i32, cid: Option<i64>) -> BobResult<DatabaseAction<EventRoomConfig>> {
+        use crate::database::schema::event_rooms::dsl::*;
+
+        if let Some(er) = EventRoomConfig::get_raw(gid)? {
+            let result = diesel::update(event_rooms.find(er.guild_id))
+                .set((preset_name.eq(name), lead_time.eq(time), category_id.eq(cid)))
+                .get_result::<EventRoomConfig>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Event Room information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let er = EventRoomConfig {
+                guild_id: gid,
+                preset_name: name,
+                lead_time: time,
+                category_id: cid,
+            };
+
+            let result = diesel::insert_into(event_rooms)
+                .values(&er)
+                .get_result::<EventRoomConfig>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Event Room information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [EventRoomConfig] struct for the given guild id.
+    fn unset_raw(gid: i64) -> BobResult<DatabaseAction<EventRoomConfig>> {
+        use crate::database::schema::event_rooms::dsl::*;
+
+        match EventRoomConfig::get_raw(gid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(er) => {
+                diesel::delete(event_rooms.find(er.guild_id)).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Event Room information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+
+    /// Unset the raw [EventRoomConfig] struct for the given guild id, if it refers to the given preset.
+    fn unset_all_raw(gid: i64, name: &str) -> BobResult<usize> {
+        use crate::database::schema::event_rooms::dsl::*;
+
+        diesel::delete(event_rooms.filter(guild_id.eq(gid).and(preset_name.eq(name))))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete Event Room information in the database.")
+    }
+
+    /// Get the time before the start of an event at which its room should be built.
+    pub fn lead_time(&self) -> BobResult<Duration> {
+        Duration::bobfrom(self.lead_time)
+    }
+
+    /// Get the category event rooms should be built in, if one was set.
+    pub fn category(&self) -> BobResult<Option<ChannelId>> {
+        self.category_id.map(ChannelId::bobfrom).transpose()
+    }
+}
+
+pub trait WithEventRooms {
+    /// Get how rooms should be built for the scheduled events of the [GuildId], if they should be built at all.
+    fn get_event_room_config(&self) -> BobResult<Option<EventRoomConfig>>;
+
+    /// Build rooms for the scheduled events of the [GuildId] using the given preset.
+    fn set_event_room_config(&self, name: String, lead_time: Duration, category: Option<ChannelId>) -> BobResult<DatabaseAction<EventRoomConfig>>;
+
+    /// Stop building rooms for the scheduled events of the [GuildId].
+    fn unset_event_room_config(&self) -> BobResult<DatabaseAction<EventRoomConfig>>;
+
+    /// Get the [ChannelId] of the room built for the given scheduled event, if there is one.
+    fn get_event_room(&self, event: ScheduledEventId) -> BobResult<Option<ChannelId>>;
+}
+
+impl WithEventRooms for GuildId {
+    fn get_event_room_config(&self) -> BobResult<Option<EventRoomConfig>> {
+        EventRoomConfig::get_raw(i64::bobfrom(*self)?)
+    }
+
+    fn set_event_room_config(&self, name: String, lead_time: Duration, category: Option<ChannelId>) -> BobResult<DatabaseAction<EventRoomConfig>> {
+        EventRoomConfig::set_raw(
+            i64::bobfrom(*self)?,
+            name,
+            i32::bobfrom(lead_time)?,
+            category.map(i64::bobfrom).transpose()?,
+        )
+    }
+
+    fn unset_event_room_config(&self) -> BobResult<DatabaseAction<EventRoomConfig>> {
+        EventRoomConfig::unset_raw(i64::bobfrom(*self)?)
+    }
+
+    fn get_event_room(&self, event: ScheduledEventId) -> BobResult<Option<ChannelId>> {
+        match CreatedChannel::get_by_event_raw(i64::bobfrom(*self)?, i64::bobfrom(event)?)? {
+            Some(cc) => Ok(Some(ChannelId::bobfrom(cc.channel_id)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// What should be built alongside each channel built by Bob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompanionMode {
+    /// A text channel in the same category, with the same permissions.
+    Channel,
+    /// A private thread in the command channel.
+    Thread,
+}
+
+impl CompanionMode {
+    /// The string used to store the [CompanionMode] in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompanionMode::Channel => "channel",
+            CompanionMode::Thread => "thread",
+        }
+    }
+
+    /// Parse the string used to store the [CompanionMode] in the database.
+    pub fn parse(value: &str) -> BobResult<Self> {
+        match value {
+            "channel" => Ok(CompanionMode::Channel),
+            "thread" => Ok(CompanionMode::Thread),
+            _ => Err(BobError::from_msg(ErrorKind::Developer, "Invalid companion mode.")),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="companion_modes"]
+pub struct CompanionModeConfig {
+    pub guild_id: i64,
+    pub companion_mode: String,
+}
+
+impl CompanionModeConfig {
+    /// Get the raw [CompanionModeConfig] struct for the given guild id.
+    fn get_raw(gid: i64) -> BobResult<Option<CompanionModeConfig>> {
+        use crate::database::schema::companion_modes::dsl::*;
+
+        let mut results: Vec<CompanionModeConfig> = companion_modes
+            .filter(guild_id.eq(gid))
+            .limit(1)
+            .load::<CompanionModeConfig>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Companion Mode information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [CompanionModeConfig] struct for the given guild id.
+    fn set_raw(gid: i64, mode: &str) -> BobResult<DatabaseAction<CompanionModeConfig>> {
+        use crate::database::schema::companion_modes::dsl::*;
+
+        if let Some(cm) = CompanionModeConfig::get_raw(gid)? {
+            let result = diesel::update(companion_modes.find(cm.guild_id))
+                .set(companion_mode.eq(mode))
+                .get_result::<CompanionModeConfig>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Companion Mode information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let cm = CompanionModeConfig {
+                guild_id: gid,
+                companion_mode: mode.to_string(),
+            };
+
+            let result = diesel::insert_into(companion_modes)
+                .values(&cm)
+                .get_result::<CompanionModeConfig>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Companion Mode information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [CompanionModeConfig] struct for the given guild id.
+    fn unset_raw(gid: i64) -> BobResult<DatabaseAction<CompanionModeConfig>> {
+        use crate::database::schema::companion_modes::dsl::*;
+
+        match CompanionModeConfig::get_raw(gid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(cm) => {
+                diesel::delete(companion_modes.find(cm.guild_id)).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Companion Mode information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+}
+
+pub trait WithCompanionMode {
+    /// Get what should be built alongside each channel built in the [GuildId], if anything.
+    fn get_companion_mode(&self) -> BobResult<Option<CompanionMode>>;
+
+    /// Build a companion alongside each channel built in the [GuildId].
+    fn set_companion_mode(&self, mode: CompanionMode) -> BobResult<DatabaseAction<CompanionModeConfig>>;
+
+    /// Stop building companions alongside the channels built in the [GuildId].
+    fn unset_companion_mode(&self) -> BobResult<DatabaseAction<CompanionModeConfig>>;
+}
+
+impl WithCompanionMode for GuildId {
+    fn get_companion_mode(&self) -> BobResult<Option<CompanionMode>> {
+        match CompanionModeConfig::get_raw(i64::bobfrom(*self)?)? {
+            Some(cm) => Ok(Some(CompanionMode::parse(&cm.companion_mode)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn set_companion_mode(&self, mode: CompanionMode) -> BobResult<DatabaseAction<CompanionModeConfig>> {
+        CompanionModeConfig::set_raw(i64::bobfrom(*self)?, mode.as_str())
+    }
+
+    fn unset_companion_mode(&self) -> BobResult<DatabaseAction<CompanionModeConfig>> {
+        CompanionModeConfig::unset_raw(i64::bobfrom(*self)?)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="archive_channels"]
+pub struct ArchiveChannel {
+    pub guild_id: i64,
+    pub channel_id: i64,
+}
+
+impl ArchiveChannel {
+    /// Get the raw [ArchiveChannel] struct for the given guild id.
+    fn get_raw(gid: i64) -> BobResult<Option<ArchiveChannel>> {
+        use crate::database::schema::archive_channels::dsl::*;
+
+        let mut results: Vec<ArchiveChannel> = archive_channels
+            .filter(guild_id.eq(gid))
+            .limit(1)
+            .load::<ArchiveChannel>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Archive Channel information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [ArchiveChannel] struct for the given guild id.
+    fn set_raw(gid: i64, cid: i64) -> BobResult<DatabaseAction<ArchiveChannel>> {
+        use crate::database::schema::archive_channels::dsl::*;
+
+        if let Some(ac) = ArchiveChannel::get_raw(gid)? {
+            let result = diesel::update(archive_channels.find(ac.guild_id))
+                .set(channel_id.eq(cid))
+                .get_result::<ArchiveChannel>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Archive Channel information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let ac = ArchiveChannel {
+                guild_id: gid,
+                channel_id: cid,
+            };
+
+            let result = diesel::insert_into(archive_channels)
+                .values(&ac)
+                .get_result::<ArchiveChannel>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Archive Channel information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [ArchiveChannel] struct for the given guild id.
+    fn unset_raw(gid: i64) -> BobResult<DatabaseAction<ArchiveChannel>> {
+        use crate::database::schema::archive_channels::dsl::*;
+
+        match ArchiveChannel::get_raw(gid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(ac) => {
+                diesel::delete(archive_channels.find(ac.guild_id)).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't unset Archive Channel in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+}
+
+pub trait WithArchiveChannel {
+    /// Get the [ChannelId] where the transcripts of the deleted channels of the [GuildId] should be sent.
+    fn get_archive_channel(&self) -> BobResult<Option<ChannelId>>;
+
+    /// Either set or unset the archive channel for the given [GuildId].
+    fn edit_archive_channel(&self, cid: Option<ChannelId>) -> BobResult<DatabaseAction<ArchiveChannel>>;
+}
+
+impl WithArchiveChannel for GuildId {
+    fn get_archive_channel(&self) -> BobResult<Option<ChannelId>> {
+        match ArchiveChannel::get_raw(i64::bobfrom(*self)?)? {
+            None => Ok(None),
+            Some(v) => Ok(Some(ChannelId::bobfrom(v.channel_id)?))
+        }
+    }
+
+    fn edit_archive_channel(&self, cid: Option<ChannelId>) -> BobResult<DatabaseAction<ArchiveChannel>> {
+        let gid = i64::bobfrom(*self)?;
+
+        match cid {
+            Some(cid) => ArchiveChannel::set_raw(gid, i64::bobfrom(cid)?),
+            None => ArchiveChannel::unset_raw(gid),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="room_limits"]
+pub struct RoomLimits {
+    pub guild_id: i64,
+    /// The maximum number of channels each member can own at the same time.
+    pub per_member: Option<i32>,
+    /// The maximum number of channels Bob can create in each category.
+    pub per_category: Option<i32>,
+    /// The maximum number of channels Bob can create in the whole guild.
+    pub per_guild: Option<i32>,
+}
+
+impl RoomLimits {
+    /// Get the raw [RoomLimits] struct for the given guild id.
+    fn get_raw(gid: i64) -> BobResult<Option<RoomLimits>> {
+        use crate::database::schema::room_limits::dsl::*;
+
+        let mut results: Vec<RoomLimits> = room_limits
+            .filter(guild_id.eq(gid))
+            .limit(1)
+            .load::<RoomLimits>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Room Limits information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [RoomLimits] struct for the given guild id.
+    fn set_raw(gid: i64, member: Option<i32>, category: Option<i32>, guild: Option<i32>) -> BobResult<DatabaseAction<RoomLimits>> {
+        use crate::database::schema::room_limits::dsl::*;
+
+        if let Some(rl) = RoomLimits::get_raw(gid)? {
+            let result = diesel::update(room_limits.find(rl.guild_id))
+                .set((per_member.eq(member), per_category.eq(category), per_guild.eq(guild)))
+                .get_result::<RoomLimits>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Room Limits information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let rl = RoomLimits {
+                guild_id: gid,
+                per_member: member,
+                per_category: category,
+                per_guild: guild,
+            };
+
+            let result = diesel::insert_into(room_limits)
+                .values(&rl)
+                .get_result::<RoomLimits>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Room Limits information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Convert one of the limits into a number of channels.
+    fn limit(value: Option<i32>) -> Option<usize> {
+        value.and_then(|v| usize::try_from(v).ok())
+    }
+
+    /// Get the maximum number of channels each member can own at the same time.
+    pub fn member_limit(&self) -> Option<usize> {
+        RoomLimits::limit(self.per_member)
+    }
+
+    /// Get the maximum number of channels Bob can create in each category.
+    pub fn category_limit(&self) -> Option<usize> {
+        RoomLimits::limit(self.per_category)
+    }
+
+    /// Get the maximum number of channels Bob can create in the whole guild.
+    pub fn guild_limit(&self) -> Option<usize> {
+        RoomLimits::limit(self.per_guild)
+    }
+}
+
+pub trait WithRoomLimits {
+    /// Get the limits on the number of channels Bob can create in the [GuildId], if any was set.
+    fn get_room_limits(&self) -> BobResult<Option<RoomLimits>>;
+
+    /// Set the limits on the number of channels Bob can create in the [GuildId]; [None] means no limit.
+    fn set_room_limits(&self, member: Option<u32>, category: Option<u32>, guild: Option<u32>) -> BobResult<DatabaseAction<RoomLimits>>;
+}
+
+impl WithRoomLimits for GuildId {
+    fn get_room_limits(&self) -> BobResult<Option<RoomLimits>> {
+        RoomLimits::get_raw(i64::bobfrom(*self)?)
+    }
+
+    fn set_room_limits(&self, member: Option<u32>, category: Option<u32>, guild: Option<u32>) -> BobResult<DatabaseAction<RoomLimits>> {
+        let convert = |value: Option<u32>| -> BobResult<Option<i32>> {
+            value.map(|v| i32::try_from(v).bob_catch(ErrorKind::User, "Limit is too large.")).transpose()
+        };
+
+        RoomLimits::set_raw(i64::bobfrom(*self)?, convert(member)?, convert(category)?, convert(guild)?)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="cooldown_exemptions"]
+pub struct CooldownExemption {
+    pub guild_id: i64,
+    pub role_id: i64,
+}
+
+impl CooldownExemption {
+    /// Get all the raw [CooldownExemption] structs for the given guild id.
+    fn get_all_raw(gid: i64) -> BobResult<Vec<CooldownExemption>> {
+        use crate::database::schema::cooldown_exemptions::dsl::*;
+
+        cooldown_exemptions
+            .filter(guild_id.eq(gid))
+            .load::<CooldownExemption>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Cooldown Exemptions from the database.")
+    }
+
+    /// Add a raw [CooldownExemption] struct for the given guild id and role id.
+    fn put_raw(gid: i64, rid: i64) -> BobResult<DatabaseAction<CooldownExemption>> {
+        use crate::database::schema::cooldown_exemptions::dsl::*;
+
+        let ce = CooldownExemption {
+            guild_id: gid,
+            role_id: rid,
+        };
+
+        let result = diesel::insert_into(cooldown_exemptions)
+            .values(&ce)
+            .on_conflict_do_nothing()
+            .get_results::<CooldownExemption>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't add a Cooldown Exemption into the database.")?;
+
+        match result.into_iter().next() {
+            Some(ce) => Ok(DatabaseAction::Created(ce)),
+            None => Ok(DatabaseAction::None),
+        }
+    }
+
+    /// Delete the raw [CooldownExemption] struct for the given guild id and role id.
+    fn delete_raw(gid: i64, rid: i64) -> BobResult<DatabaseAction<CooldownExemption>> {
+        use crate::database::schema::cooldown_exemptions::dsl::*;
+
+        let deleted = diesel::delete(cooldown_exemptions.find((gid, rid)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete a Cooldown Exemption from the database.")?;
+
+        match deleted {
+            0 => Ok(DatabaseAction::None),
+            _ => Ok(DatabaseAction::Deleted),
+        }
+    }
+}
+
+pub trait WithCooldownExemptions {
+    /// Get the [RoleId]s of the roles whose members aren't subject to cooldowns in the [GuildId].
+    fn get_cooldown_exemptions(&self) -> BobResult<Vec<RoleId>>;
+
+    /// Exempt the members of the given role from cooldowns, or stop exempting them.
+    fn set_cooldown_exemption(&self, role: RoleId, exempt: bool) -> BobResult<DatabaseAction<CooldownExemption>>;
+}
+
+impl WithCooldownExemptions for GuildId {
+    fn get_cooldown_exemptions(&self) -> BobResult<Vec<RoleId>> {
+        CooldownExemption::get_all_raw(i64::bobfrom(*self)?)?
+            .into_iter()
+            .map(|ce| RoleId::bobfrom(ce.role_id))
+            .collect()
+    }
+
+    fn set_cooldown_exemption(&self, role: RoleId, exempt: bool) -> BobResult<DatabaseAction<CooldownExemption>> {
+        let gid = i64::bobfrom(*self)?;
+        let rid = i64::bobfrom(role)?;
+
+        match exempt {
+            true => CooldownExemption::put_raw(gid, rid),
+            false => CooldownExemption::delete_raw(gid, rid),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="overflow_categories"]
+pub struct OverflowCategory {
+    pub guild_id: i64,
+    pub category_id: i64,
+    /// The id of the category where channels should be built when the category is full.
+    pub overflow_id: i64,
+}
+
+impl OverflowCategory {
+    /// Get the raw [OverflowCategory] struct for the given guild id and category id.
+    fn get_raw(gid: i64, cid: i64) -> BobResult<Option<OverflowCategory>> {
+        use crate::database::schema::overflow_categories::dsl::*;
+
+        let mut results: Vec<OverflowCategory> = overflow_categories
+            .filter(guild_id.eq(gid).and(category_id.eq(cid)))
+            .limit(1)
+            .load::<OverflowCategory>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Overflow Category information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [OverflowCategory] struct for the given guild id and category id.
+    fn set_raw(gid: i64, cid: i64, oid: i64) -> BobResult<DatabaseAction<OverflowCategory>> {
+        use crate::database::schema::overflow_categories::dsl::*;
+
+        if let Some(oc) = OverflowCategory::get_raw(gid, cid)? {
+            let result = diesel::update(overflow_categories.find((oc.guild_id, oc.category_id)))
+                .set(overflow_id.eq(oid))
+                .get_result::<OverflowCategory>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Overflow Category information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let oc = OverflowCategory {
+                guild_id: gid,
+                category_id: cid,
+                overflow_id: oid,
+            };
+
+            let result = diesel::insert_into(overflow_categories)
+                .values(&oc)
+                .get_result::<OverflowCategory>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Overflow Category information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [OverflowCategory] struct for the given guild id and category id.
+    fn unset_raw(gid: i64, cid: i64) -> BobResult<DatabaseAction<OverflowCategory>> {
+        use crate::database::schema::overflow_categories::dsl::*;
+
+        match OverflowCategory::get_raw(gid, cid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(oc) => {
+                diesel::delete(overflow_categories.find((oc.guild_id, oc.category_id))).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Overflow Category information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+}
+
+pub trait WithOverflowCategories {
+    /// Get the [ChannelId] of the category where channels should be built when the given category is full.
+    fn get_overflow_category(&self, category: ChannelId) -> BobResult<Option<ChannelId>>;
+
+    /// Either set or unset the category where channels should be built when the given category is full.
+    fn edit_overflow_category(&self, category: ChannelId, overflow: Option<ChannelId>) -> BobResult<DatabaseAction<OverflowCategory>>;
+}
+
+impl WithOverflowCategories for GuildId {
+    fn get_overflow_category(&self, category: ChannelId) -> BobResult<Option<ChannelId>> {
+        match OverflowCategory::get_raw(i64::bobfrom(*self)?, i64::bobfrom(category)?)? {
+            Some(oc) => Ok(Some(ChannelId::bobfrom(oc.overflow_id)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn edit_overflow_category(&self, category: ChannelId, overflow: Option<ChannelId>) -> BobResult<DatabaseAction<OverflowCategory>> {
+        let gid = i64::bobfrom(*self)?;
+        let cid = i64::bobfrom(category)?;
+
+        match overflow {
+            Some(overflow) => OverflowCategory::set_raw(gid, cid, i64::bobfrom(overflow)?),
+            None => OverflowCategory::unset_raw(gid, cid),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="build_categories"]
+pub struct BuildCategory {
+    pub guild_id: i64,
+    pub category_id: i64,
+}
+
+impl BuildCategory {
+    /// Get all the raw [BuildCategory] structs for the given guild id.
+    fn get_all_raw(gid: i64) -> BobResult<Vec<BuildCategory>> {
+        use crate::database::schema::build_categories::dsl::*;
+
+        build_categories
+            .filter(guild_id.eq(gid))
+            .load::<BuildCategory>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Build Categories from the database.")
+    }
+
+    /// Add a raw [BuildCategory] struct for the given guild id and category id.
+    fn put_raw(gid: i64, cid: i64) -> BobResult<DatabaseAction<BuildCategory>> {
+        use crate::database::schema::build_categories::dsl::*;
+
+        let bc = BuildCategory {
+            guild_id: gid,
+            category_id: cid,
+        };
+
+        let result = diesel::insert_into(build_categories)
+            .values(&bc)
+            .on_conflict_do_nothing()
+            .get_results::<BuildCategory>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't add a Build Category into the database.")?;
+
+        match result.into_iter().next() {
+            Some(bc) => Ok(DatabaseAction::Created(bc)),
+            None => Ok(DatabaseAction::None),
+        }
+    }
+
+    /// Delete the raw [BuildCategory] struct for the given guild id and category id.
+    fn delete_raw(gid: i64, cid: i64) -> BobResult<DatabaseAction<BuildCategory>> {
+        use crate::database::schema::build_categories::dsl::*;
+
+        let deleted = diesel::delete(build_categories.find((gid, cid)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete a Build Category from the database.")?;
+
+        match deleted {
+            0 => Ok(DatabaseAction::None),
+            _ => Ok(DatabaseAction::Deleted),
+        }
+    }
+}
+
+pub trait WithBuildCategories {
+    /// Get the [ChannelId]s of the categories where `/build` can be used in the [GuildId].
+    ///
+    /// If the list is empty, `/build` can be used in any category.
+    fn get_build_categories(&self) -> BobResult<Vec<ChannelId>>;
+
+    /// Allow or disallow building channels in the given category.
+    fn set_build_category(&self, category: ChannelId, allowed: bool) -> BobResult<DatabaseAction<BuildCategory>>;
+}
+
+impl WithBuildCategories for GuildId {
+    fn get_build_categories(&self) -> BobResult<Vec<ChannelId>> {
+        BuildCategory::get_all_raw(i64::bobfrom(*self)?)?
+            .into_iter()
+            .map(|bc| ChannelId::bobfrom(bc.category_id))
+            .collect()
+    }
+
+    fn set_build_category(&self, category: ChannelId, allowed: bool) -> BobResult<DatabaseAction<BuildCategory>> {
+        let gid = i64::bobfrom(*self)?;
+        let cid = i64::bobfrom(category)?;
+
+        match allowed {
+            true => BuildCategory::put_raw(gid, cid),
+            false => BuildCategory::delete_raw(gid, cid),
+        }
+    }
+}
+
+/// How the channels built by Bob should be ordered in a category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomOrdering {
+    /// Right below an anchor channel, from the oldest to the newest.
+    Anchor,
+    /// In alphabetical order, at the bottom of the category.
+    Alphabetical,
+    /// From the oldest to the newest, at the bottom of the category.
+    Creation,
+}
+
+impl RoomOrdering {
+    /// The string used to store the [RoomOrdering] in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoomOrdering::Anchor => "anchor",
+            RoomOrdering::Alphabetical => "alphabetical",
+            RoomOrdering::Creation => "creation",
+        }
+    }
+
+    /// Parse the string used to store the [RoomOrdering] in the database.
+    pub fn parse(value: &str) -> BobResult<Self> {
+        match value {
+            "anchor" => Ok(RoomOrdering::Anchor),
+            "alphabetical" => Ok(RoomOrdering::Alphabetical),
+            "creation" => Ok(RoomOrdering::Creation),
+            _ => Err(BobError::from_msg(ErrorKind::Developer, "Invalid room ordering.")),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="category_orderings"]
+pub struct CategoryOrdering {
+    pub guild_id: i64,
+    pub category_id: i64,
+    pub ordering: String,
+    /// The id of the channel the rooms should be placed below, if the ordering is [RoomOrdering::Anchor].
+    pub anchor_id: Option<i64>,
+}
+
+impl CategoryOrdering {
+    /// Get the raw [CategoryOrdering] struct for the given guild id and category id.
+    fn get_raw(gid: i64, cid: i64) -> BobResult<Option<CategoryOrdering>> {
+        use crate::database::schema::category_orderings::dsl::*;
+
+        let mut results: Vec<CategoryOrdering> = category_orderings
+            .filter(guild_id.eq(gid).and(category_id.eq(cid)))
+            .limit(1)
+            .load::<CategoryOrdering>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Category Ordering information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [CategoryOrdering] struct for the given guild id and category id.
+    fn set_raw(gid: i64, cid: i64, ord: &str, aid: Option<i64>) -> BobResult<DatabaseAction<CategoryOrdering>> {
+        use crate::database::schema::category_orderings::dsl::*;
+
+        if let Some(co) = CategoryOrdering::get_raw(gid, cid)? {
+            let result = diesel::update(category_orderings.find((co.guild_id, co.category_id)))
+                .set((ordering.eq(ord), anchor_id.eq(aid)))
+                .get_result::<CategoryOrdering>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Category Ordering information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let co = CategoryOrdering {
+                guild_id: gid,
+                category_id: cid,
+                ordering: ord.to_string(),
+                anchor_id: aid,
+            };
+
+            let result = diesel::insert_into(category_orderings)
+                .values(&co)
+                .get_result::<CategoryOrdering>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Category Ordering information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [CategoryOrdering] struct for the given guild id and category id.
+    fn unset_raw(gid: i64, cid: i64) -> BobResult<DatabaseAction<CategoryOrdering>> {
+        use crate::database::schema::category_orderings::dsl::*;
+
+        match CategoryOrdering::get_raw(gid, cid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(co) => {
+                diesel::delete(category_orderings.find((co.guild_id, co.category_id))).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Category Ordering information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+
+    /// Get the [RoomOrdering] of the category.
+    pub fn ordering(&self) -> BobResult<RoomOrdering> {
+        RoomOrdering::parse(&self.ordering)
+    }
+
+    /// Get the [ChannelId] of the anchor channel of the category, if there is one.
+    pub fn anchor(&self) -> BobResult<Option<ChannelId>> {
+        self.anchor_id.map(ChannelId::bobfrom).transpose()
+    }
+}
+
+pub trait WithCategoryOrderings {
+    /// Get how the channels built by Bob should be ordered in the given category.
+    fn get_category_ordering(&self, category: ChannelId) -> BobResult<Option<CategoryOrdering>>;
+
+    /// Set how the channels built by Bob should be ordered in the given category.
+    fn set_category_ordering(&self, category: ChannelId, ordering: RoomOrdering, anchor: Option<ChannelId>) -> BobResult<DatabaseAction<CategoryOrdering>>;
+
+    /// Stop ordering the channels built by Bob in the given category.
+    fn unset_category_ordering(&self, category: ChannelId) -> BobResult<DatabaseAction<CategoryOrdering>>;
+}
+
+impl WithCategoryOrderings for GuildId {
+    fn get_category_ordering(&self, category: ChannelId) -> BobResult<Option<CategoryOrdering>> {
+        CategoryOrdering::get_raw(i64::bobfrom(*self)?, i64::bobfrom(category)?)
+    }
+
+    fn set_category_ordering(&self, category: ChannelId, ordering: RoomOrdering, anchor: Option<ChannelId>) -> BobResult<DatabaseAction<CategoryOrdering>> {
+        let anchor = anchor.map(i64::bobfrom).transpose()?;
+        CategoryOrdering::set_raw(i64::bobfrom(*self)?, i64::bobfrom(category)?, ordering.as_str(), anchor)
+    }
+
+    fn unset_category_ordering(&self, category: ChannelId) -> BobResult<DatabaseAction<CategoryOrdering>> {
+        CategoryOrdering::unset_raw(i64::bobfrom(*self)?, i64::bobfrom(category)?)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="command_visibilities"]
+pub struct CommandVisibility {
+    pub guild_id: i64,
+    pub command: String,
+    /// Whether the successful responses of the command should only be visible to whoever called it.
+    pub ephemeral: bool,
+}
+
+impl CommandVisibility {
+    /// Get the raw [CommandVisibility] struct for the given guild id and command name.
+    fn get_raw(gid: i64, cmd: &str) -> BobResult<Option<CommandVisibility>> {
+        use crate::database::schema::command_visibilities::dsl::*;
+
+        let mut results: Vec<CommandVisibility> = command_visibilities
+            .filter(guild_id.eq(gid).and(command.eq(cmd)))
+            .limit(1)
+            .load::<CommandVisibility>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Command Visibility information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [CommandVisibility] struct for the given guild id and command name.
+    fn set_raw(gid: i64, cmd: &str, eph: bool) -> BobResult<DatabaseAction<CommandVisibility>> {
+        use crate::database::schema::command_visibilities::dsl::*;
+
+        if let Some(cv) = CommandVisibility::get_raw(gid, cmd)? {
+            let result = diesel::update(command_visibilities.find((cv.guild_id, cv.command)))
+                .set(ephemeral.eq(eph))
+                .get_result::<CommandVisibility>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Command Visibility information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let cv = CommandVisibility {
+                guild_id: gid,
+                command: cmd.to_string(),
+                ephemeral: eph,
+            };
+
+            let result = diesel::insert_into(command_visibilities)
+                .values(&cv)
+                .get_result::<CommandVisibility>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Command Visibility information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [CommandVisibility] struct for the given guild id and command name.
+    fn unset_raw(gid: i64, cmd: &str) -> BobResult<DatabaseAction<CommandVisibility>> {
+        use crate::database::schema::command_visibilities::dsl::*;
+
+        match CommandVisibility::get_raw(gid, cmd)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(cv) => {
+                diesel::delete(command_visibilities.find((cv.guild_id, cv.command))).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Command Visibility information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+}
+
+pub trait WithCommandVisibilities {
+    /// Get whether the successful responses of the given command should only be visible to whoever called it, if it
+    /// has been set in the [GuildId].
+    fn get_command_visibility(&self, command: &str) -> BobResult<Option<bool>>;
+
+    /// Either set or unset whether the successful responses of the given command should only be visible to whoever
+    /// called it.
+    fn edit_command_visibility(&self, command: &str, ephemeral: Option<bool>) -> BobResult<DatabaseAction<CommandVisibility>>;
+}
+
+impl WithCommandVisibilities for GuildId {
+    fn get_command_visibility(&self, command: &str) -> BobResult<Option<bool>> {
+        Ok(CommandVisibility::get_raw(i64::bobfrom(*self)?, command)?.map(|cv| cv.ephemeral))
+    }
+
+    fn edit_command_visibility(&self, command: &str, ephemeral: Option<bool>) -> BobResult<DatabaseAction<CommandVisibility>> {
+        let gid = i64::bobfrom(*self)?;
+
+        match ephemeral {
+            Some(ephemeral) => CommandVisibility::set_raw(gid, command, ephemeral),
+            None => CommandVisibility::unset_raw(gid, command),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="builder_roles"]
+pub struct BuilderRole {
+    pub guild_id: i64,
+    pub role_id: i64,
+}
+
+impl BuilderRole {
+    /// Get all the raw [BuilderRole] structs for the given guild id.
+    fn get_all_raw(gid: i64) -> BobResult<Vec<BuilderRole>> {
+        use crate::database::schema::builder_roles::dsl::*;
+
+        builder_roles
+            .filter(guild_id.eq(gid))
+            .load::<BuilderRole>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Builder Roles from the database.")
+    }
+
+    /// Add a raw [BuilderRole] struct for the given guild id and role id.
+    fn put_raw(gid: i64, rid: i64) -> BobResult<DatabaseAction<BuilderRole>> {
+        use crate::database::schema::builder_roles::dsl::*;
+
+        let br = BuilderRole {
+            guild_id: gid,
+            role_id: rid,
+        };
+
+        let result = diesel::insert_into(builder_roles)
+            .values(&br)
+            .on_conflict_do_nothing()
+            .get_results::<BuilderRole>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't add a Builder Role into the database.")?;
+
+        match result.into_iter().next() {
+            Some(br) => Ok(DatabaseAction::Created(br)),
+            None => Ok(DatabaseAction::None),
+        }
+    }
+
+    /// Delete the raw [BuilderRole] struct for the given guild id and role id.
+    fn delete_raw(gid: i64, rid: i64) -> BobResult<DatabaseAction<BuilderRole>> {
+        use crate::database::schema::builder_roles::dsl::*;
+
+        let deleted = diesel::delete(builder_roles.find((gid, rid)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete a Builder Role from the database.")?;
+
+        match deleted {
+            0 => Ok(DatabaseAction::None),
+            _ => Ok(DatabaseAction::Deleted),
+        }
+    }
+
+    /// Delete all the raw [BuilderRole] structs for the given guild id.
+    fn delete_all_raw(gid: i64) -> BobResult<DatabaseAction<BuilderRole>> {
+        use crate::database::schema::builder_roles::dsl::*;
+
+        let deleted = diesel::delete(builder_roles.filter(guild_id.eq(gid)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete the Builder Roles from the database.")?;
+
+        match deleted {
+            0 => Ok(DatabaseAction::None),
+            _ => Ok(DatabaseAction::Deleted),
+        }
+    }
+}
+
+pub trait WithBuilderRoles {
+    /// Get the [RoleId]s of the roles whose members can build channels in the [GuildId].
+    ///
+    /// If the list is empty, everyone can build channels.
+    fn get_builder_roles(&self) -> BobResult<Vec<RoleId>>;
+
+    /// Allow the members of the given role to build channels, or stop allowing them.
+    fn set_builder_role(&self, role: RoleId, allowed: bool) -> BobResult<DatabaseAction<BuilderRole>>;
+
+    /// Allow everyone to build channels, forgetting all the builder roles.
+    fn unset_builder_roles(&self) -> BobResult<DatabaseAction<BuilderRole>>;
+}
+
+impl WithBuilderRoles for GuildId {
+    fn get_builder_roles(&self) -> BobResult<Vec<RoleId>> {
+        BuilderRole::get_all_raw(i64::bobfrom(*self)?)?
+            .into_iter()
+            .map(|br| RoleId::bobfrom(br.role_id))
+            .collect()
+    }
+
+    fn set_builder_role(&self, role: RoleId, allowed: bool) -> BobResult<DatabaseAction<BuilderRole>> {
+        let gid = i64::bobfrom(*self)?;
+        let rid = i64::bobfrom(role)?;
+
+        match allowed {
+            true => BuilderRole::put_raw(gid, rid),
+            false => BuilderRole::delete_raw(gid, rid),
+        }
+    }
+
+    fn unset_builder_roles(&self) -> BobResult<DatabaseAction<BuilderRole>> {
+        BuilderRole::delete_all_raw(i64::bobfrom(*self)?)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="build_bans"]
+pub struct BuildBan {
+    pub guild_id: i64,
+    pub user_id: i64,
+    /// The unix timestamp after which the ban expires, or [None] if it's permanent.
+    pub expires_at: Option<i64>,
+    pub reason: Option<String>,
+}
+
+impl BuildBan {
+    /// Get the raw [BuildBan] struct for the given guild id and user id.
+    fn get_raw(gid: i64, uid: i64) -> BobResult<Option<BuildBan>> {
+        use crate::database::schema::build_bans::dsl::*;
+
+        let mut results: Vec<BuildBan> = build_bans
+            .filter(guild_id.eq(gid).and(user_id.eq(uid)))
+            .limit(1)
+            .load::<BuildBan>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Build Ban information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [BuildBan] struct for the given guild id and user id.
+    fn set_raw(gid: i64, uid: i64, exp: Option<i64>, rsn: Option<String>) -> BobResult<DatabaseAction<BuildBan>> {
+        use crate::database::schema::build_bans::dsl::*;
+
+        if let Some(bb) = BuildBan::get_raw(gid, uid)? {
+            let result = diesel::update(build_bans.find((bb.guild_id, bb.user_id)))
+                .set((expires_at.eq(exp), reason.eq(rsn)))
+                .get_result::<BuildBan>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Build Ban information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let bb = BuildBan {
+                guild_id: gid,
+                user_id: uid,
+                expires_at: exp,
+                reason: rsn,
+            };
+
+            let result = diesel::insert_into(build_bans)
+                .values(&bb)
+                .get_result::<BuildBan>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Build Ban information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [BuildBan] struct for the given guild id and user id.
+    fn unset_raw(gid: i64, uid: i64) -> BobResult<DatabaseAction<BuildBan>> {
+        use crate::database::schema::build_bans::dsl::*;
+
+        let deleted = diesel::delete(build_bans.find((gid, uid)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete Build Ban information from the database.")?;
+
+        match deleted {
+            0 => Ok(DatabaseAction::None),
+            _ => Ok(DatabaseAction::Deleted),
+        }
+    }
+
+    /// Check whether the ban has expired at the given unix timestamp.
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expires_at, Some(expires_at) if expires_at <= now)
+    }
+}
+
+pub trait WithBuildBans {
+    /// Get the [BuildBan] preventing the given user from building channels in the [GuildId], if it hasn't expired.
+    ///
+    /// Expired bans are forgotten along the way.
+    fn get_build_ban(&self, user: UserId) -> BobResult<Option<BuildBan>>;
+
+    /// Prevent the given user from building channels, until the given time or forever.
+    fn set_build_ban(&self, user: UserId, expires_at: Option<SystemTime>, reason: Option<String>) -> BobResult<DatabaseAction<BuildBan>>;
+
+    /// Allow the given user to build channels again.
+    fn unset_build_ban(&self, user: UserId) -> BobResult<DatabaseAction<BuildBan>>;
+}
+
+impl WithBuildBans for GuildId {
+    fn get_build_ban(&self, user: UserId) -> BobResult<Option<BuildBan>> {
+        let gid = i64::bobfrom(*self)?;
+        let uid = i64::bobfrom(user)?;
+
+        let now = i64::bobfrom(SystemTime::now())?;
+
+        match BuildBan::get_raw(gid, uid)? {
+            Some(bb) if bb.is_expired(now) => {
+                BuildBan::unset_raw(gid, uid)?;
+                Ok(None)
+            },
+            result => Ok(result),
+        }
+    }
+
+    fn set_build_ban(&self, user: UserId, expires_at: Option<SystemTime>, reason: Option<String>) -> BobResult<DatabaseAction<BuildBan>> {
+        let expires_at = expires_at.map(i64::bobfrom).transpose()?;
+        BuildBan::set_raw(i64::bobfrom(*self)?, i64::bobfrom(user)?, expires_at, reason)
+    }
+
+    fn unset_build_ban(&self, user: UserId) -> BobResult<DatabaseAction<BuildBan>> {
+        BuildBan::unset_raw(i64::bobfrom(*self)?, i64::bobfrom(user)?)
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="blocked_names"]
+pub struct BlockedName {
+    pub guild_id: i64,
+    /// A case-insensitive regular expression that channel names mustn't match.
+    pub pattern: String,
+}
+
+impl BlockedName {
+    /// Get all the raw [BlockedName] structs for the given guild id.
+    fn get_all_raw(gid: i64) -> BobResult<Vec<BlockedName>> {
+        use crate::database::schema::blocked_names::dsl::*;
+
+        blocked_names
+            .filter(guild_id.eq(gid))
+            .load::<BlockedName>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Blocked Names from the database.")
+    }
+
+    /// Add a raw [BlockedName] struct for the given guild id and pattern.
+    fn put_raw(gid: i64, pat: &str) -> BobResult<DatabaseAction<BlockedName>> {
+        use crate::database::schema::blocked_names::dsl::*;
+
+        let bn = BlockedName {
+            guild_id: gid,
+            pattern: pat.to_string(),
+        };
+
+        let result = diesel::insert_into(blocked_names)
+            .values(&bn)
+            .on_conflict_do_nothing()
+            .get_results::<BlockedName>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't add a Blocked Name into the database.")?;
+
+        match result.into_iter().next() {
+            Some(bn) => Ok(DatabaseAction::Created(bn)),
+            None => Ok(DatabaseAction::None),
+        }
+    }
+
+    /// Delete the raw [BlockedName] struct for the given guild id and pattern.
+    fn delete_raw(gid: i64, pat: &str) -> BobResult<DatabaseAction<BlockedName>> {
+        use crate::database::schema::blocked_names::dsl::*;
+
+        let deleted = diesel::delete(blocked_names.find((gid, pat)))
+            .execute(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't delete a Blocked Name from the database.")?;
+
+        match deleted {
+            0 => Ok(DatabaseAction::None),
+            _ => Ok(DatabaseAction::Deleted),
+        }
+    }
+}
+
+pub trait WithNameBlocklist {
+    /// Get the patterns that the names of the channels built in the [GuildId] mustn't match.
+    fn get_name_blocklist(&self) -> BobResult<Vec<String>>;
+
+    /// Add the given pattern to the blocklist, or remove it.
+    fn set_blocked_name(&self, pattern: &str, blocked: bool) -> BobResult<DatabaseAction<BlockedName>>;
+}
+
+impl WithNameBlocklist for GuildId {
+    fn get_name_blocklist(&self) -> BobResult<Vec<String>> {
+        Ok(
+            BlockedName::get_all_raw(i64::bobfrom(*self)?)?
+                .into_iter()
+                .map(|bn| bn.pattern)
+                .collect()
+        )
+    }
+
+    fn set_blocked_name(&self, pattern: &str, blocked: bool) -> BobResult<DatabaseAction<BlockedName>> {
+        let gid = i64::bobfrom(*self)?;
+
+        match blocked {
+            true => BlockedName::put_raw(gid, pattern),
+            false => BlockedName::delete_raw(gid, pattern),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="moderation_channels"]
+pub struct ModerationChannel {
+    pub guild_id: i64,
+    pub channel_id: i64,
+}
+
+impl ModerationChannel {
+    /// Get the raw [ModerationChannel] struct for the given guild id.
+    fn get_raw(gid: i64) -> BobResult<Option<ModerationChannel>> {
+        use crate::database::schema::moderation_channels::dsl::*;
+
+        let mut results: Vec<ModerationChannel> = moderation_channels
+            .filter(guild_id.eq(gid))
+            .limit(1)
+            .load::<ModerationChannel>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Moderation Channel information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [ModerationChannel] struct for the given guild id.
+    fn set_raw(gid: i64, cid: i64) -> BobResult<DatabaseAction<ModerationChannel>> {
+        use crate::database::schema::moderation_channels::dsl::*;
+
+        if let Some(mc) = ModerationChannel::get_raw(gid)? {
+            let result = diesel::update(moderation_channels.find(mc.guild_id))
+                .set(channel_id.eq(cid))
+                .get_result::<ModerationChannel>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Moderation Channel information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let mc = ModerationChannel {
+                guild_id: gid,
+                channel_id: cid,
+            };
+
+            let result = diesel::insert_into(moderation_channels)
+                .values(&mc)
+                .get_result::<ModerationChannel>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Moderation Channel information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [ModerationChannel] struct for the given guild id.
+    fn unset_raw(gid: i64) -> BobResult<DatabaseAction<ModerationChannel>> {
+        use crate::database::schema::moderation_channels::dsl::*;
+
+        match ModerationChannel::get_raw(gid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(mc) => {
+                diesel::delete(moderation_channels.find(mc.guild_id)).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't unset Moderation Channel in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+}
+
+pub trait WithModerationChannel {
+    /// Get the [ChannelId] where the rejected builds and renames of the [GuildId] should be logged.
+    fn get_moderation_channel(&self) -> BobResult<Option<ChannelId>>;
+
+    /// Either set or unset the moderation channel for the given [GuildId].
+    fn edit_moderation_channel(&self, cid: Option<ChannelId>) -> BobResult<DatabaseAction<ModerationChannel>>;
+}
+
+impl WithModerationChannel for GuildId {
+    fn get_moderation_channel(&self) -> BobResult<Option<ChannelId>> {
+        match ModerationChannel::get_raw(i64::bobfrom(*self)?)? {
+            None => Ok(None),
+            Some(v) => Ok(Some(ChannelId::bobfrom(v.channel_id)?))
+        }
+    }
+
+    fn edit_moderation_channel(&self, cid: Option<ChannelId>) -> BobResult<DatabaseAction<ModerationChannel>> {
+        let gid = i64::bobfrom(*self)?;
+
+        match cid {
+            Some(cid) => ModerationChannel::set_raw(gid, i64::bobfrom(cid)?),
+            None => ModerationChannel::unset_raw(gid),
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name="guild_departures"]
+pub struct GuildDeparture {
+    pub guild_id: i64,
+    pub departed_at: i64,
+}
+
+impl GuildDeparture {
+    /// Get all the raw [GuildDeparture] structs.
+    fn get_all_raw() -> BobResult<Vec<GuildDeparture>> {
+        use crate::database::schema::guild_departures::dsl::*;
+
+        guild_departures
+            .load::<GuildDeparture>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Guild Departures from the database.")
+    }
+
+    /// Get the raw [GuildDeparture] struct for the given guild id.
+    fn get_raw(gid: i64) -> BobResult<Option<GuildDeparture>> {
+        use crate::database::schema::guild_departures::dsl::*;
+
+        let mut results: Vec<GuildDeparture> = guild_departures
+            .filter(guild_id.eq(gid))
+            .limit(1)
+            .load::<GuildDeparture>(&connect())
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Guild Departure information from the database.")?;
+
+        match results.len() {
+            0 => Ok(None),
+            _ => Ok(Some(results.swap_remove(0)))
+        }
+    }
+
+    /// Set the raw [GuildDeparture] struct for the given guild id.
+    fn set_raw(gid: i64, time: i64) -> BobResult<DatabaseAction<GuildDeparture>> {
+        use crate::database::schema::guild_departures::dsl::*;
+
+        if let Some(gd) = GuildDeparture::get_raw(gid)? {
+            let result = diesel::update(guild_departures.find(gd.guild_id))
+                .set(departed_at.eq(time))
+                .get_result::<GuildDeparture>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Guild Departure information in the database.")?;
+
+            Ok(DatabaseAction::Updated(result))
+        }
+        else {
+            let gd = GuildDeparture {
+                guild_id: gid,
+                departed_at: time,
+            };
+
+            let result = diesel::insert_into(guild_departures)
+                .values(&gd)
+                .get_result::<GuildDeparture>(&connect())
+                .bob_catch(ErrorKind::External, "Couldn't edit Guild Departure information in the database.")?;
+
+            Ok(DatabaseAction::Created(result))
+        }
+    }
+
+    /// Unset the raw [GuildDeparture] struct for the given guild id.
+    fn unset_raw(gid: i64) -> BobResult<DatabaseAction<GuildDeparture>> {
+        use crate::database::schema::guild_departures::dsl::*;
+
+        match GuildDeparture::get_raw(gid)? {
+            None => {
+                Ok(DatabaseAction::None)
+            },
+            Some(gd) => {
+                diesel::delete(guild_departures.find(gd.guild_id)).execute(&connect())
+                    .bob_catch(ErrorKind::External, "Couldn't delete Guild Departure information in the database.")?;
+                Ok(DatabaseAction::Deleted)
+            },
+        }
+    }
+}
+
+pub trait WithGuildDeparture {
+    /// Get the time the bot was removed from the given [GuildId], if it is waiting to be purged.
+    fn get_departure(&self) -> BobResult<Option<SystemTime>>;
+
+    /// Set the time the bot was removed from the given [GuildId].
+    fn set_departure(&self, time: SystemTime) -> BobResult<DatabaseAction<GuildDeparture>>;
+
+    /// Unset the departure time of the given [GuildId], cancelling its purge.
+    fn unset_departure(&self) -> BobResult<DatabaseAction<GuildDeparture>>;
+}
+
+impl WithGuildDeparture for GuildId {
+    fn get_departure(&self) -> BobResult<Option<SystemTime>> {
+        let gid = i64::bobfrom(*self)?;
+
+        match GuildDeparture::get_raw(gid)? {
+            None => Ok(None),
+            Some(v) => Ok(Some(SystemTime::bobfrom(v.departed_at)?))
+        }
+    }
+
+    fn set_departure(&self, time: SystemTime) -> BobResult<DatabaseAction<GuildDeparture>> {
+        let gid = i64::bobfrom(*self)?;
+        let time = i64::bobfrom(time)?;
+
+        GuildDeparture::set_raw(gid, time)
+    }
+
+    fn unset_departure(&self) -> BobResult<DatabaseAction<GuildDeparture>> {
+        let gid = i64::bobfrom(*self)?;
+
+        GuildDeparture::unset_raw(gid)
+    }
+}
+
+/// Get all the guilds the bot was removed from, along with the time they were left at.
+pub fn get_departures() -> BobResult<Vec<(GuildId, SystemTime)>> {
+    GuildDeparture::get_all_raw()?
+        .into_iter()
+        .map(|gd| Ok((GuildId::bobfrom(gd.guild_id)?, SystemTime::bobfrom(gd.departed_at)?)))
+        .collect()
+}
+
+/// Get all the guilds which have at least a row stored in the database.
+pub fn get_known_guilds() -> BobResult<BTreeSet<GuildId>> {
+    let connection = connect();
+
+    let mut gids: Vec<i64> = vec![];
+    gids.append(
+        &mut command_channels::table.select(command_channels::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Command Channels from the database.")?
+    );
+    gids.append(
+        &mut deletion_times::table.select(deletion_times::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Deletion Times from the database.")?
+    );
+    gids.append(
+        &mut archive_channels::table.select(archive_channels::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Archive Channels from the database.")?
+    );
+    gids.append(
+        &mut channels_created::table.select(channels_created::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Created Channels from the database.")?
+    );
+    gids.append(
+        &mut presets::table.select(presets::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Presets from the database.")?
+    );
+    gids.append(
+        &mut preset_revisions::table.select(preset_revisions::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Preset Revisions from the database.")?
+    );
+    gids.append(
+        &mut companion_modes::table.select(companion_modes::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Companion Modes from the database.")?
+    );
+    gids.append(
+        &mut cooldown_exemptions::table.select(cooldown_exemptions::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Cooldown Exemptions from the database.")?
+    );
+    gids.append(
+        &mut default_presets::table.select(default_presets::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Default Presets from the database.")?
+    );
+    gids.append(
+        &mut event_rooms::table.select(event_rooms::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Event Rooms from the database.")?
+    );
+    gids.append(
+        &mut room_limits::table.select(room_limits::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Room Limits from the database.")?
+    );
+    gids.append(
+        &mut overflow_categories::table.select(overflow_categories::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Overflow Categories from the database.")?
+    );
+    gids.append(
+        &mut build_categories::table.select(build_categories::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Build Categories from the database.")?
+    );
+    gids.append(
+        &mut category_orderings::table.select(category_orderings::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Category Orderings from the database.")?
+    );
+    gids.append(
+        &mut command_visibilities::table.select(command_visibilities::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Command Visibilities from the database.")?
+    );
+    gids.append(
+        &mut builder_roles::table.select(builder_roles::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Builder Roles from the database.")?
+    );
+    gids.append(
+        &mut build_bans::table.select(build_bans::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Build Bans from the database.")?
+    );
+    gids.append(
+        &mut blocked_names::table.select(blocked_names::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Blocked Names from the database.")?
+    );
+    gids.append(
+        &mut moderation_channels::table.select(moderation_channels::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Moderation Channels from the database.")?
+    );
+    gids.append(
+        &mut guild_departures::table.select(guild_departures::guild_id).load::<i64>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Guild Departures from the database.")?
+    );
+
+    gids.into_iter()
+        .map(GuildId::bobfrom)
+        .collect()
+}
+
+pub trait WithGuildData {
+    /// Delete everything stored in the database about the given [GuildId].
+    fn purge_guild_data(&self) -> BobResult<()>;
+}
+
+impl WithGuildData for GuildId {
+    fn purge_guild_data(&self) -> BobResult<()> {
+        let gid = i64::bobfrom(*self)?;
+        let connection = connect();
+
+        connection.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(command_channels::table.filter(command_channels::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(deletion_times::table.filter(deletion_times::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(archive_channels::table.filter(archive_channels::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(channels_created::table.filter(channels_created::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(presets::table.filter(presets::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(preset_revisions::table.filter(preset_revisions::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(cooldown_exemptions::table.filter(cooldown_exemptions::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(default_presets::table.filter(default_presets::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(event_rooms::table.filter(event_rooms::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(companion_modes::table.filter(companion_modes::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(room_limits::table.filter(room_limits::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(overflow_categories::table.filter(overflow_categories::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(build_categories::table.filter(build_categories::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(category_orderings::table.filter(category_orderings::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(command_visibilities::table.filter(command_visibilities::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(builder_roles::table.filter(builder_roles::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(build_bans::table.filter(build_bans::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(blocked_names::table.filter(blocked_names::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(moderation_channels::table.filter(moderation_channels::guild_id.eq(gid))).execute(&connection)?;
+            diesel::delete(guild_departures::table.filter(guild_departures::guild_id.eq(gid))).execute(&connection)?;
+            Ok(())
+        }).bob_catch(ErrorKind::External, "Couldn't purge Guild data from the database.")
+    }
+}
+
+
+/// All the settings of a guild, apart from its presets, used to export them or to replace them all at once.
+#[derive(Debug, Clone, Default)]
+pub struct GuildSettings {
+    pub command_channel: Option<ChannelId>,
+    pub deletion_time: Option<Duration>,
+    /// The default presets of the categories, or of the whole guild if the category is [None].
+    pub default_presets: Vec<(Option<ChannelId>, String)>,
+    /// The preset, lead time and category used to build the rooms of scheduled events.
+    pub event_rooms: Option<(String, Duration, Option<ChannelId>)>,
+    pub companion_mode: Option<CompanionMode>,
+    pub archive_channel: Option<ChannelId>,
+    /// The limits per member, per category and per guild.
+    pub room_limits: (Option<u32>, Option<u32>, Option<u32>),
+    pub cooldown_exemptions: Vec<RoleId>,
+    /// The categories which overflow, along with the category they overflow into.
+    pub overflow_categories: Vec<(ChannelId, ChannelId)>,
+    pub build_categories: Vec<ChannelId>,
+    pub category_orderings: Vec<(ChannelId, RoomOrdering, Option<ChannelId>)>,
+    pub command_visibilities: Vec<(String, bool)>,
+    pub builder_roles: Vec<RoleId>,
+    pub build_bans: Vec<(UserId, Option<SystemTime>, Option<String>)>,
+    pub blocked_names: Vec<String>,
+    pub moderation_channel: Option<ChannelId>,
+}
+
+/// A preset saved by [WithGuildSettings::replace_guild_settings], along with its author and its locks.
+#[derive(Debug, Clone)]
+pub struct ImportedPreset {
+    pub name: String,
+    pub data: PresetData,
+    pub author: Option<UserId>,
+    pub locked: bool,
+    pub allowed_roles: Vec<RoleId>,
+}
+
+pub trait WithGuildSettings {
+    /// Get all the settings of the [GuildId].
+    fn get_guild_settings(&self) -> BobResult<GuildSettings>;
+
+    /// Replace all the settings of the [GuildId], and save the given presets over the existing ones with the same
+    /// name, in a single transaction.
+    fn replace_guild_settings(&self, settings: &GuildSettings, presets: &[ImportedPreset]) -> BobResult<()>;
+}
+
+impl WithGuildSettings for GuildId {
+    fn get_guild_settings(&self) -> BobResult<GuildSettings> {
+        let gid = i64::bobfrom(*self)?;
+        let connection = connect();
+
+        let command_channel = command_channels::table.filter(command_channels::guild_id.eq(gid))
+            .load::<CommandChannel>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Command Channel information from the database.")?;
+        let deletion_time = deletion_times::table.filter(deletion_times::guild_id.eq(gid))
+            .load::<DeletionTime>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Deletion Time information from the database.")?;
+        let default_presets = default_presets::table.filter(default_presets::guild_id.eq(gid))
+            .load::<DefaultPreset>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Default Presets from the database.")?;
+        let event_rooms = event_rooms::table.filter(event_rooms::guild_id.eq(gid))
+            .load::<EventRoomConfig>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Event Rooms from the database.")?;
+        let companion_mode = companion_modes::table.filter(companion_modes::guild_id.eq(gid))
+            .load::<CompanionModeConfig>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Companion Modes from the database.")?;
+        let archive_channel = archive_channels::table.filter(archive_channels::guild_id.eq(gid))
+            .load::<ArchiveChannel>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Archive Channels from the database.")?;
+        let room_limits = room_limits::table.filter(room_limits::guild_id.eq(gid))
+            .load::<RoomLimits>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Room Limits from the database.")?;
+        let cooldown_exemptions = cooldown_exemptions::table.filter(cooldown_exemptions::guild_id.eq(gid))
+            .load::<CooldownExemption>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Cooldown Exemptions from the database.")?;
+        let overflow_categories = overflow_categories::table.filter(overflow_categories::guild_id.eq(gid))
+            .load::<OverflowCategory>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Overflow Categories from the database.")?;
+        let build_categories = build_categories::table.filter(build_categories::guild_id.eq(gid))
+            .load::<BuildCategory>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Build Categories from the database.")?;
+        let category_orderings = category_orderings::table.filter(category_orderings::guild_id.eq(gid))
+            .load::<CategoryOrdering>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Category Orderings from the database.")?;
+        let command_visibilities = command_visibilities::table.filter(command_visibilities::guild_id.eq(gid))
+            .load::<CommandVisibility>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Command Visibilities from the database.")?;
+        let builder_roles = builder_roles::table.filter(builder_roles::guild_id.eq(gid))
+            .load::<BuilderRole>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Builder Roles from the database.")?;
+        let build_bans = build_bans::table.filter(build_bans::guild_id.eq(gid))
+            .load::<BuildBan>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Build Bans from the database.")?;
+        let blocked_names = blocked_names::table.filter(blocked_names::guild_id.eq(gid))
+            .load::<BlockedName>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Blocked Names from the database.")?;
+        let moderation_channel = moderation_channels::table.filter(moderation_channels::guild_id.eq(gid))
+            .load::<ModerationChannel>(&connection)
+            .bob_catch(ErrorKind::External, "Couldn't retrieve Moderation Channels from the database.")?;
+
+        let optional_channel = |cid: Option<i64>| cid.map(ChannelId::bobfrom).transpose();
+        let limit = |value: Option<i32>| value.and_then(|v| u32::try_from(v).ok());
+
+        Ok(GuildSettings {
+            command_channel: command_channel.first().map(|cc| ChannelId::bobfrom(cc.channel_id)).transpose()?,
+            deletion_time: deletion_time.first().map(|dt| Duration::bobfrom(dt.deletion_time)).transpose()?,
+            default_presets: default_presets.into_iter()
+                .map(|dp| Ok((
+                    match dp.scope_id == gid {
+                        true => None,
+                        false => Some(ChannelId::bobfrom(dp.scope_id)?),
+                    },
+                    dp.preset_name,
+                )))
+                .collect::<BobResult<_>>()?,
+            event_rooms: match event_rooms.into_iter().next() {
+                Some(er) => Some((er.preset_name.clone(), er.lead_time()?, er.category()?)),
+                None => None,
+            },
+            companion_mode: companion_mode.first().map(|cm| CompanionMode::parse(&cm.companion_mode)).transpose()?,
+            archive_channel: archive_channel.first().map(|ac| ChannelId::bobfrom(ac.channel_id)).transpose()?,
+            room_limits: match room_limits.first() {
+                Some(rl) => (limit(rl.per_member), limit(rl.per_category), limit(rl.per_guild)),
+                None => (None, None, None),
+            },
+            cooldown_exemptions: cooldown_exemptions.iter().map(|ce| RoleId::bobfrom(ce.role_id)).collect::<BobResult<_>>()?,
+            overflow_categories: overflow_categories.iter()
+                .map(|oc| Ok((ChannelId::bobfrom(oc.category_id)?, ChannelId::bobfrom(oc.overflow_id)?)))
+                .collect::<BobResult<_>>()?,
+            build_categories: build_categories.iter().map(|bc| ChannelId::bobfrom(bc.category_id)).collect::<BobResult<_>>()?,
+            category_orderings: category_orderings.iter()
+                .map(|co| Ok((ChannelId::bobfrom(co.category_id)?, RoomOrdering::parse(&co.ordering)?, optional_channel(co.anchor_id)?)))
+                .collect::<BobResult<_>>()?,
+            command_visibilities: command_visibilities.into_iter().map(|cv| (cv.command, cv.ephemeral)).collect(),
+            builder_roles: builder_roles.iter().map(|br| RoleId::bobfrom(br.role_id)).collect::<BobResult<_>>()?,
+            build_bans: build_bans.into_iter()
+                .map(|bb| Ok((UserId::bobfrom(bb.user_id)?, bb.expires_at.map(SystemTime::bobfrom).transpose()?, bb.reason)))
+                .collect::<BobResult<_>>()?,
+            blocked_names: blocked_names.into_iter().map(|bn| bn.pattern).collect(),
+            moderation_channel: moderation_channel.first().map(|mc| ChannelId::bobfrom(mc.channel_id)).transpose()?,
+        })
+    }
+
+    fn replace_guild_settings(&self, settings: &GuildSettings, presets: &[ImportedPreset]) -> BobResult<()> {
+        let gid = i64::bobfrom(*self)?;
+        let channel = |cid: ChannelId| i64::bobfrom(cid);
+        let limit = |value: Option<u32>| -> BobResult<Option<i32>> {
+            value.map(|v| i32::try_from(v).bob_catch(ErrorKind::User, "Limit is too large.")).transpose()
+        };
+
+        // Convert everything before starting the transaction, so that it only has to deal with database errors
+        let command_channel: Vec<CommandChannel> = settings.command_channel.iter()
+            .map(|cid| Ok(CommandChannel { guild_id: gid, channel_id: channel(*cid)? }))
+            .collect::<BobResult<_>>()?;
+        let deletion_time: Vec<DeletionTime> = settings.deletion_time.iter()
+            .map(|dt| Ok(DeletionTime { guild_id: gid, deletion_time: i32::bobfrom(*dt)? }))
+            .collect::<BobResult<_>>()?;
+        let default_presets: Vec<DefaultPreset> = settings.default_presets.iter()
+            .map(|(category, name)| Ok(DefaultPreset {
+                guild_id: gid,
+                scope_id: match category {
+                    Some(category) => channel(*category)?,
+                    None => gid,
+                },
+                preset_name: name.clone(),
+            }))
+            .collect::<BobResult<_>>()?;
+        let event_rooms: Vec<EventRoomConfig> = settings.event_rooms.iter()
+            .map(|(name, lead_time, category)| Ok(EventRoomConfig {
+                guild_id: gid,
+                preset_name: name.clone(),
+                lead_time: i32::bobfrom(*lead_time)?,
+                category_id: category.map(channel).transpose()?,
+            }))
+            .collect::<BobResult<_>>()?;
+        let companion_mode: Vec<CompanionModeConfig> = settings.companion_mode.iter()
+            .map(|cm| CompanionModeConfig { guild_id: gid, companion_mode: cm.as_str().to_string() })
+            .collect();
+        let archive_channel: Vec<ArchiveChannel> = settings.archive_channel.iter()
+            .map(|cid| Ok(ArchiveChannel { guild_id: gid, channel_id: channel(*cid)? }))
+            .collect::<BobResult<_>>()?;
+        let room_limits: Vec<RoomLimits> = match settings.room_limits {
+            (None, None, None) => vec![],
+            (member, category, guild) => vec![RoomLimits {
+                guild_id: gid,
+                per_member: limit(member)?,
+                per_category: limit(category)?,
+                per_guild: limit(guild)?,
+            }],
+        };
+        let cooldown_exemptions: Vec<CooldownExemption> = settings.cooldown_exemptions.iter()
+            .map(|rid| Ok(CooldownExemption { guild_id: gid, role_id: i64::bobfrom(*rid)? }))
+            .collect::<BobResult<_>>()?;
+        let overflow_categories: Vec<OverflowCategory> = settings.overflow_categories.iter()
+            .map(|(category, overflow)| Ok(OverflowCategory { guild_id: gid, category_id: channel(*category)?, overflow_id: channel(*overflow)? }))
+            .collect::<BobResult<_>>()?;
+        let build_categories: Vec<BuildCategory> = settings.build_categories.iter()
+            .map(|cid| Ok(BuildCategory { guild_id: gid, category_id: channel(*cid)? }))
+            .collect::<BobResult<_>>()?;
+        let category_orderings: Vec<CategoryOrdering> = settings.category_orderings.iter()
+            .map(|(category, ordering, anchor)| Ok(CategoryOrdering {
+                guild_id: gid,
+                category_id: channel(*category)?,
+                ordering: ordering.as_str().to_string(),
+                anchor_id: anchor.map(channel).transpose()?,
+            }))
+            .collect::<BobResult<_>>()?;
+        let command_visibilities: Vec<CommandVisibility> = settings.command_visibilities.iter()
+            .map(|(command, ephemeral)| CommandVisibility { guild_id: gid, command: command.clone(), ephemeral: *ephemeral })
+            .collect();
+        let builder_roles: Vec<BuilderRole> = settings.builder_roles.iter()
+            .map(|rid| Ok(BuilderRole { guild_id: gid, role_id: i64::bobfrom(*rid)? }))
+            .collect::<BobResult<_>>()?;
+        let build_bans: Vec<BuildBan> = settings.build_bans.iter()
+            .map(|(uid, expires_at, reason)| Ok(BuildBan {
+                guild_id: gid,
+                user_id: i64::bobfrom(*uid)?,
+                expires_at: expires_at.map(i64::bobfrom).transpose()?,
+                reason: reason.clone(),
+            }))
+            .collect::<BobResult<_>>()?;
+        let blocked_names: Vec<BlockedName> = settings.blocked_names.iter()
+            .map(|pattern| BlockedName { guild_id: gid, pattern: pattern.clone() })
+            .collect();
+        let moderation_channel: Vec<ModerationChannel> = settings.moderation_channel.iter()
+            .map(|cid| Ok(ModerationChannel { guild_id: gid, channel_id: channel(*cid)? }))
+            .collect::<BobResult<_>>()?;
+        let preset_rows: Vec<Preset> = presets.iter()
+            .map(|preset| Ok(Preset {
+                guild_id: gid,
+                preset_name: preset.name.clone(),
+                preset_data: serde_json::to_value(&preset.data).bob_catch(ErrorKind::Developer, "Couldn't serialize PresetData.")?,
+                author_id: preset.author.map(i64::bobfrom).transpose()?,
+                locked: preset.locked,
+                allowed_roles: preset.allowed_roles.iter().map(|rid| i64::bobfrom(*rid)).collect::<BobResult<_>>()?,
+            }))
+            .collect::<BobResult<_>>()?;
+        let history_size = preset_history_size()?;
+        let now = i64::bobfrom(SystemTime::now())?;
+
+        let connection = connect();
+
+        connection.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(command_channels::table.filter(command_channels::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(command_channels::table).values(&command_channel).execute(&connection)?;
+            diesel::delete(deletion_times::table.filter(deletion_times::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(deletion_times::table).values(&deletion_time).execute(&connection)?;
+            diesel::delete(companion_modes::table.filter(companion_modes::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(companion_modes::table).values(&companion_mode).execute(&connection)?;
+            diesel::delete(archive_channels::table.filter(archive_channels::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(archive_channels::table).values(&archive_channel).execute(&connection)?;
+            diesel::delete(room_limits::table.filter(room_limits::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(room_limits::table).values(&room_limits).execute(&connection)?;
+            diesel::delete(cooldown_exemptions::table.filter(cooldown_exemptions::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(cooldown_exemptions::table).values(&cooldown_exemptions).execute(&connection)?;
+            diesel::delete(overflow_categories::table.filter(overflow_categories::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(overflow_categories::table).values(&overflow_categories).execute(&connection)?;
+            diesel::delete(build_categories::table.filter(build_categories::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(build_categories::table).values(&build_categories).execute(&connection)?;
+            diesel::delete(category_orderings::table.filter(category_orderings::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(category_orderings::table).values(&category_orderings).execute(&connection)?;
+            diesel::delete(command_visibilities::table.filter(command_visibilities::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(command_visibilities::table).values(&command_visibilities).execute(&connection)?;
+            diesel::delete(builder_roles::table.filter(builder_roles::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(builder_roles::table).values(&builder_roles).execute(&connection)?;
+            diesel::delete(build_bans::table.filter(build_bans::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(build_bans::table).values(&build_bans).execute(&connection)?;
+            diesel::delete(blocked_names::table.filter(blocked_names::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(blocked_names::table).values(&blocked_names).execute(&connection)?;
+            diesel::delete(moderation_channels::table.filter(moderation_channels::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(moderation_channels::table).values(&moderation_channel).execute(&connection)?;
+
+            // Presets are saved before the settings referring to them
+            for preset in preset_rows.iter() {
+                diesel::insert_into(presets::table)
+                    .values(preset)
+                    .on_conflict((presets::guild_id, presets::preset_name))
+                    .do_update()
+                    .set((
+                        presets::preset_data.eq(&preset.preset_data),
+                        presets::author_id.eq(preset.author_id),
+                        presets::locked.eq(preset.locked),
+                        presets::allowed_roles.eq(&preset.allowed_roles),
+                    ))
+                    .execute(&connection)?;
+
+                let latest = preset_revisions::table
+                    .filter(preset_revisions::guild_id.eq(gid).and(preset_revisions::preset_name.eq(&preset.preset_name)))
+                    .select(diesel::dsl::max(preset_revisions::revision))
+                    .first::<Option<i32>>(&connection)?
+                    .unwrap_or(0);
+
+                diesel::insert_into(preset_revisions::table)
+                    .values(&PresetRevision {
+                        guild_id: gid,
+                        preset_name: preset.preset_name.clone(),
+                        revision: latest + 1,
+                        preset_data: preset.preset_data.clone(),
+                        author_id: None,
+                        created_at: now,
+                    })
+                    .execute(&connection)?;
+
+                diesel::delete(
+                    preset_revisions::table.filter(
+                        preset_revisions::guild_id.eq(gid)
+                            .and(preset_revisions::preset_name.eq(&preset.preset_name))
+                            .and(preset_revisions::revision.le(latest + 1 - history_size))
+                    )
+                ).execute(&connection)?;
+            }
+
+            diesel::delete(default_presets::table.filter(default_presets::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(default_presets::table).values(&default_presets).execute(&connection)?;
+            diesel::delete(event_rooms::table.filter(event_rooms::guild_id.eq(gid))).execute(&connection)?;
+            diesel::insert_into(event_rooms::table).values(&event_rooms).execute(&connection)?;
+            Ok(())
+        }).bob_catch(ErrorKind::External, "Couldn't replace the Guild settings in the database.")
+    }
+}
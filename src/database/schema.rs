@@ -1,7 +1,56 @@
+table! {
+    archive_channels (guild_id) {
+        guild_id -> Int8,
+        channel_id -> Int8,
+    }
+}
+
+table! {
+    blocked_names (guild_id, pattern) {
+        guild_id -> Int8,
+        pattern -> Varchar,
+    }
+}
+
+table! {
+    build_bans (guild_id, user_id) {
+        guild_id -> Int8,
+        user_id -> Int8,
+        expires_at -> Nullable<Int8>,
+        reason -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    build_categories (guild_id, category_id) {
+        guild_id -> Int8,
+        category_id -> Int8,
+    }
+}
+
+table! {
+    builder_roles (guild_id, role_id) {
+        guild_id -> Int8,
+        role_id -> Int8,
+    }
+}
+
+table! {
+    category_orderings (guild_id, category_id) {
+        guild_id -> Int8,
+        category_id -> Int8,
+        ordering -> Varchar,
+        anchor_id -> Nullable<Int8>,
+    }
+}
+
 table! {
     channels_created (guild_id, channel_id) {
         guild_id -> Int8,
         channel_id -> Int8,
+        owner_id -> Nullable<Int8>,
+        event_id -> Nullable<Int8>,
+        companion_id -> Nullable<Int8>,
     }
 }
 
@@ -12,6 +61,36 @@ table! {
     }
 }
 
+table! {
+    command_visibilities (guild_id, command) {
+        guild_id -> Int8,
+        command -> Varchar,
+        ephemeral -> Bool,
+    }
+}
+
+table! {
+    companion_modes (guild_id) {
+        guild_id -> Int8,
+        companion_mode -> Varchar,
+    }
+}
+
+table! {
+    cooldown_exemptions (guild_id, role_id) {
+        guild_id -> Int8,
+        role_id -> Int8,
+    }
+}
+
+table! {
+    default_presets (guild_id, scope_id) {
+        guild_id -> Int8,
+        scope_id -> Int8,
+        preset_name -> Varchar,
+    }
+}
+
 table! {
     deletion_times (guild_id) {
         guild_id -> Int8,
@@ -19,17 +98,87 @@ table! {
     }
 }
 
+table! {
+    event_rooms (guild_id) {
+        guild_id -> Int8,
+        preset_name -> Varchar,
+        lead_time -> Int4,
+        category_id -> Nullable<Int8>,
+    }
+}
+
+table! {
+    guild_departures (guild_id) {
+        guild_id -> Int8,
+        departed_at -> Int8,
+    }
+}
+
+table! {
+    moderation_channels (guild_id) {
+        guild_id -> Int8,
+        channel_id -> Int8,
+    }
+}
+
+table! {
+    overflow_categories (guild_id, category_id) {
+        guild_id -> Int8,
+        category_id -> Int8,
+        overflow_id -> Int8,
+    }
+}
+
+table! {
+    preset_revisions (guild_id, preset_name, revision) {
+        guild_id -> Int8,
+        preset_name -> Varchar,
+        revision -> Int4,
+        preset_data -> Jsonb,
+        author_id -> Nullable<Int8>,
+        created_at -> Int8,
+    }
+}
+
 table! {
     presets (guild_id, preset_name) {
         guild_id -> Int8,
         preset_name -> Varchar,
         preset_data -> Jsonb,
+        author_id -> Nullable<Int8>,
+        locked -> Bool,
+        allowed_roles -> Array<Int8>,
+    }
+}
+
+table! {
+    room_limits (guild_id) {
+        guild_id -> Int8,
+        per_member -> Nullable<Int4>,
+        per_category -> Nullable<Int4>,
+        per_guild -> Nullable<Int4>,
     }
 }
 
 allow_tables_to_appear_in_same_query!(
+    archive_channels,
+    blocked_names,
+    build_bans,
+    build_categories,
+    builder_roles,
+    category_orderings,
     channels_created,
     command_channels,
+    command_visibilities,
+    companion_modes,
+    cooldown_exemptions,
+    default_presets,
     deletion_times,
+    event_rooms,
+    guild_departures,
+    moderation_channels,
+    overflow_categories,
+    preset_revisions,
     presets,
+    room_limits,
 );
@@ -0,0 +1,146 @@
+//! This module classifies the errors returned by the Discord API.
+
+use std::any::{Any};
+use serenity::Error as SerenityError;
+use serenity::http::StatusCode;
+use serenity::http::error::{Error as HttpError, DiscordJsonError};
+use crate::errors::{ErrorKind};
+
+
+/// The JSON error code returned when a channel doesn't exist.
+const UNKNOWN_CHANNEL: isize = 10003;
+/// The JSON error code returned when a member isn't in the guild.
+const UNKNOWN_MEMBER: isize = 10007;
+/// The JSON error code returned when a message doesn't exist.
+const UNKNOWN_MESSAGE: isize = 10008;
+/// The JSON error code returned when a role doesn't exist.
+const UNKNOWN_ROLE: isize = 10011;
+/// The JSON error code returned when a scheduled event doesn't exist.
+pub const UNKNOWN_SCHEDULED_EVENT: isize = 10070;
+/// The JSON error code returned when the guild has reached the maximum number of channels.
+const MAX_CHANNELS_REACHED: isize = 30013;
+/// The JSON error code returned when trying to move a member who isn't connected to voice.
+const TARGET_NOT_CONNECTED: isize = 40032;
+/// The JSON error code returned when Bob can't see the resource.
+const MISSING_ACCESS: isize = 50001;
+/// The JSON error code returned when Bob lacks a permission.
+const MISSING_PERMISSIONS: isize = 50013;
+/// The JSON error code returned when the request body was rejected.
+const INVALID_FORM_BODY: isize = 50035;
+
+/// The field error code returned when a category already contains 50 channels.
+const CATEGORY_FULL: &str = "CHANNEL_PARENT_MAX_CHANNELS";
+
+
+/// Classify an unsuccessful response of the Discord API from its status code and its JSON error.
+fn classify_response(status_code: StatusCode, error: &DiscordJsonError) -> Option<(ErrorKind, &'static str)> {
+    match error.code {
+        UNKNOWN_CHANNEL => return Some((ErrorKind::User, "The channel doesn't exist anymore.")),
+        UNKNOWN_MEMBER => return Some((ErrorKind::User, "That member isn't in this server anymore.")),
+        UNKNOWN_MESSAGE => return Some((ErrorKind::External, "The message doesn't exist anymore.")),
+        UNKNOWN_ROLE => return Some((ErrorKind::User, "That role doesn't exist anymore.")),
+        UNKNOWN_SCHEDULED_EVENT => return Some((ErrorKind::User, "That scheduled event doesn't exist anymore.")),
+        MAX_CHANNELS_REACHED => return Some((ErrorKind::Admin, "This server has reached the maximum number of channels.")),
+        TARGET_NOT_CONNECTED => return Some((ErrorKind::User, "You're not connected to voice chat!")),
+        MISSING_ACCESS => return Some((ErrorKind::Admin, "I can't access that channel: check my permissions on it.")),
+        MISSING_PERMISSIONS => return Some((ErrorKind::Admin, "I'm missing some permissions: check my roles and the permissions of the category.")),
+        INVALID_FORM_BODY if error.errors.iter().any(|e| e.code == CATEGORY_FULL) => {
+            return Some((ErrorKind::Admin, "The category is full: set an overflow category with `/config overflow`."))
+        },
+        _ => {},
+    }
+
+    match status_code.as_u16() {
+        429 => Some((ErrorKind::External, "Discord is rate limiting me: please try again in a bit.")),
+        401 | 403 => Some((ErrorKind::Admin, "Discord didn't allow me to do that: check my permissions.")),
+        500..=599 => Some((ErrorKind::External, "Discord is having issues: please try again later.")),
+        _ => None,
+    }
+}
+
+
+/// Classify an HTTP error of serenity.
+fn classify_http(err: &HttpError) -> Option<(ErrorKind, &'static str)> {
+    match err {
+        HttpError::UnsuccessfulRequest(response) => classify_response(response.status_code, &response.error),
+        HttpError::Request(_) => Some((ErrorKind::External, "Couldn't reach Discord: please try again later.")),
+        _ => None,
+    }
+}
+
+
+/// Find the HTTP error of serenity wrapped in the given error, if any.
+fn as_http(err: &dyn Any) -> Option<&HttpError> {
+    if let Some(SerenityError::Http(http)) = err.downcast_ref::<SerenityError>() {
+        return Some(http);
+    }
+    err.downcast_ref::<HttpError>()
+}
+
+
+/// If the given error was returned by the Discord API, find the [ErrorKind] it should be reported as, along with a
+/// message explaining it to the user.
+pub fn classify_error(err: &dyn Any) -> Option<(ErrorKind, &'static str)> {
+    classify_http(as_http(err)?)
+}
+
+
+/// If the given error is an unsuccessful response of the Discord API, get its JSON error code.
+pub fn json_error_code(err: &dyn Any) -> Option<isize> {
+    match as_http(err)? {
+        HttpError::UnsuccessfulRequest(response) => Some(response.error.code),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json};
+
+    fn json_error(code: isize, errors: &[&str]) -> DiscordJsonError {
+        let errors: serde_json::Map<_, _> = errors.iter().enumerate()
+            .map(|(i, e)| (format!("field{}", i), json!({"_errors": [{"code": e, "message": ""}]})))
+            .collect();
+        serde_json::from_value(json!({"code": code, "message": "", "errors": errors})).unwrap()
+    }
+
+    fn kind(status_code: StatusCode, error: &DiscordJsonError) -> Option<String> {
+        classify_response(status_code, error).map(|(kind, _)| format!("{:?}", kind))
+    }
+
+    #[test]
+    fn classifies_json_error_codes() {
+        assert_eq!(kind(StatusCode::NOT_FOUND, &json_error(UNKNOWN_CHANNEL, &[])), Some("User".to_string()));
+        assert_eq!(kind(StatusCode::NOT_FOUND, &json_error(UNKNOWN_SCHEDULED_EVENT, &[])), Some("User".to_string()));
+        assert_eq!(kind(StatusCode::FORBIDDEN, &json_error(MISSING_PERMISSIONS, &[])), Some("Admin".to_string()));
+        assert_eq!(kind(StatusCode::BAD_REQUEST, &json_error(MAX_CHANNELS_REACHED, &[])), Some("Admin".to_string()));
+    }
+
+    #[test]
+    fn classifies_full_categories() {
+        let full = json_error(INVALID_FORM_BODY, &["BASE_TYPE_REQUIRED", CATEGORY_FULL]);
+        assert_eq!(kind(StatusCode::BAD_REQUEST, &full), Some("Admin".to_string()));
+
+        // Other invalid bodies are a bug of Bob
+        let invalid = json_error(INVALID_FORM_BODY, &["BASE_TYPE_REQUIRED"]);
+        assert_eq!(kind(StatusCode::BAD_REQUEST, &invalid), None);
+    }
+
+    #[test]
+    fn classifies_status_codes() {
+        assert_eq!(kind(StatusCode::TOO_MANY_REQUESTS, &json_error(0, &[])), Some("External".to_string()));
+        assert_eq!(kind(StatusCode::FORBIDDEN, &json_error(0, &[])), Some("Admin".to_string()));
+        assert_eq!(kind(StatusCode::BAD_GATEWAY, &json_error(0, &[])), Some("External".to_string()));
+        assert_eq!(kind(StatusCode::BAD_REQUEST, &json_error(0, &[])), None);
+    }
+
+    #[test]
+    fn ignores_other_errors() {
+        let err = SerenityError::Other("not an HTTP error");
+        assert!(classify_error(&err).is_none());
+        assert_eq!(json_error_code(&err), None);
+        assert_eq!(json_error_code(&"not even a serenity error"), None);
+    }
+}
@@ -5,6 +5,11 @@ use serenity::http::Http;
 use indoc::indoc;
 use crate::utils::discord_display::{DiscordDisplay};
 
+pub mod report;
+pub mod discord;
+
+use self::discord::{classify_error};
+
 
 /// The four possible "causes" of an error:
 /// - `ErrorKind::User`: if an error is caused by a mistake on the bot user's part.
@@ -42,7 +47,8 @@ impl BobError {
         }
     }
 
-    pub async fn handle(&self, http: &Http, msg: &Message) -> BobResult<Message> {
+    /// Log the error with a level appropriate for its [ErrorKind].
+    pub fn log(&self) {
         match &self.knd {
             ErrorKind::User => {
                 debug!("{}", &self);
@@ -60,6 +66,10 @@ impl BobError {
                 warn!("{}", &self);
             },
         }
+    }
+
+    pub async fn handle(&self, http: &Http, msg: &Message) -> BobResult<Message> {
+        self.log();
 
         msg.reply(&http, &self.to_discord().to_string()).await
             .bob_catch(ErrorKind::Admin, "Couldn't handle error")
@@ -124,12 +134,21 @@ pub trait BobCatch<T> {
     fn bob_catch(self, knd: ErrorKind, msg: &str) -> BobResult<T>;
 }
 
+/// Errors returned by the Discord API are classified with [classify_error], overriding the given [ErrorKind] and
+/// explaining the error after the given message.
 impl<T, E: Error + Send + Sync + 'static> BobCatch<T> for Result<T, E> {
     fn bob_catch(self, knd: ErrorKind, msg: &str) -> BobResult<T> {
-        self.map_err(|err| BobError {
-            knd,
-            err: Some(Box::from(err)),
-            msg: Some(String::from(msg))
+        self.map_err(|err| match classify_error(&err) {
+            Some((knd, hint)) => BobError {
+                knd,
+                err: Some(Box::from(err)),
+                msg: Some(format!("{} {}", msg, hint))
+            },
+            None => BobError {
+                knd,
+                err: Some(Box::from(err)),
+                msg: Some(String::from(msg))
+            },
         })
     }
 }
@@ -0,0 +1,92 @@
+//! This module contains the operator error sink, which reports errors to a Discord webhook.
+
+use std::env;
+use std::error::{Error};
+use serenity::http::Http;
+use serenity::model::prelude::{Embed, GuildId};
+use crate::errors::{BobError, BobResult, BobCatch, ErrorKind};
+
+
+/// Get the URL of the webhook errors should be reported to, set through the `BOB_ERROR_WEBHOOK` environment variable.
+fn error_webhook_url() -> Option<String> {
+    env::var("BOB_ERROR_WEBHOOK").ok()
+}
+
+
+/// Where and why an error occurred, included in the report sent to the operator.
+#[derive(Debug, Clone)]
+pub struct ErrorContext {
+    /// An id shown to both the user and the operator, to match error reports together.
+    pub correlation_id: String,
+    /// The guild where the error occurred, if any.
+    pub guild_id: Option<GuildId>,
+    /// The name of the command that caused the error, if any.
+    pub command: Option<String>,
+}
+
+
+impl BobError {
+    /// Check whether the operator of the bot should be made aware of this error.
+    pub fn is_reportable(&self) -> bool {
+        matches!(&self.knd, ErrorKind::Host | ErrorKind::Developer | ErrorKind::External)
+    }
+
+    /// Describe the `err` of this error, along with all of its sources.
+    fn error_chain(&self) -> Option<String> {
+        let mut lines = vec![];
+
+        let mut current: Option<&(dyn Error + 'static)> = match &self.err {
+            Some(err) => Some(err.as_ref()),
+            None => None,
+        };
+        while let Some(err) = current {
+            lines.push(format!("{}", err));
+            current = err.source();
+        }
+
+        match lines.is_empty() {
+            true => None,
+            false => Some(lines.join("\ncaused by: ")),
+        }
+    }
+
+    /// If this error is reportable and the `BOB_ERROR_WEBHOOK` environment variable is set, send an embed describing
+    /// it to the webhook.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the error was reported.
+    /// - `Ok(false)` if the error shouldn't be reported, or if no webhook is set.
+    /// - `Err(_)` if the report couldn't be sent.
+    pub async fn report(&self, http: &Http, context: &ErrorContext) -> BobResult<bool> {
+        if !self.is_reportable() {
+            return Ok(false);
+        }
+
+        let url = match error_webhook_url() {
+            Some(url) => url,
+            None => return Ok(false),
+        };
+
+        let webhook = http.get_webhook_from_url(&url)
+            .await.bob_catch(ErrorKind::Host, "Couldn't retrieve the error webhook.")?;
+
+        let embed = Embed::fake(|e| {
+            e.title(format!("{:?} error", &self.knd));
+            e.description(self.msg.as_deref().unwrap_or("_no message_"));
+            e.field("Correlation id", format!("`{}`", &context.correlation_id), true);
+            e.field("Guild", context.guild_id.map_or_else(|| "_none_".to_string(), |g| format!("`{}`", g)), true);
+            e.field("Command", context.command.as_ref().map_or_else(|| "_none_".to_string(), |c| format!("`{}`", c)), true);
+            if let Some(chain) = self.error_chain() {
+                let chain: String = chain.chars().take(1000).collect();
+                e.field("Error", format!("```\n{}\n```", chain), false);
+            }
+            e
+        });
+
+        webhook.execute(http, false, |w| w.embeds(vec![embed]))
+            .await.bob_catch(ErrorKind::Host, "Couldn't send the error report to the webhook.")?;
+
+        Ok(true)
+    }
+}
@@ -185,12 +185,16 @@ pub trait ApplicationCommandInteractionDataHashmapExtension {
     fn req_user(&self, name: &'static str) -> BobResult<User>;
     fn req_channel(&self, name: &'static str) -> BobResult<PartialChannel>;
     fn req_role(&self, name: &'static str) -> BobResult<Role>;
+    fn req_attachment(&self, name: &'static str) -> BobResult<Attachment>;
     fn opt_string(&self, name: &'static str) -> BobResult<Option<String>>;
     fn opt_integer(&self, name: &'static str) -> BobResult<Option<i64>>;
     fn opt_boolean(&self, name: &'static str) -> BobResult<Option<bool>>;
     fn opt_user(&self, name: &'static str) -> BobResult<Option<User>>;
     fn opt_channel(&self, name: &'static str) -> BobResult<Option<PartialChannel>>;
     fn opt_role(&self, name: &'static str) -> BobResult<Option<Role>>;
+
+    /// Retrieve an optional mentionable argument, as the [PermissionOverwriteType] targeting the mentioned user or role.
+    fn opt_mentionable(&self, name: &'static str) -> BobResult<Option<PermissionOverwriteType>>;
 }
 
 
@@ -264,4 +268,14 @@ impl ApplicationCommandInteractionDataHashmapExtension for HashMap<String, Optio
     arg!(req_user,    opt_user,     User,           CommandDataOptionValue::User);
     arg!(req_channel, opt_channel,  PartialChannel, CommandDataOptionValue::Channel);
     arg!(req_role,    opt_role,     Role,           CommandDataOptionValue::Role);
+    arg_required!(req_attachment, Attachment, CommandDataOptionValue::Attachment);
+
+    fn opt_mentionable(&self, name: &str) -> BobResult<Option<PermissionOverwriteType>> {
+        match application_command_interaction_data_hashmap_extension_get_optional_arg(self, name) {
+            Some(CommandDataOptionValue::User(user, ..)) => Ok(Some(PermissionOverwriteType::Member(user.id))),
+            Some(CommandDataOptionValue::Role(role)) => Ok(Some(PermissionOverwriteType::Role(role.id))),
+            Some(_) => Err(BobError::from_msg(ErrorKind::Developer, "Argument is of an invalid type")),
+            None => Ok(None),
+        }
+    }
 }
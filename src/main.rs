@@ -14,17 +14,26 @@ mod errors;
 mod utils;
 mod extensions;
 mod commands;
+mod cli;
 
 use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
 use serenity::prelude::*;
 use serenity::model::prelude::*;
 use dotenv::{dotenv};
 use serenity::model::application::interaction::{Interaction, InteractionResponseType};
-use serenity::model::application::command::{Command, CommandOptionType};
+use serenity::model::application::command::{Command, CommandOptionType, CommandType};
 use crate::errors::*;
-use crate::tasks::clean::{maybe_clean_oc, maybe_clean_vsc};
-use crate::utils::command_router::{handle_command_interaction};
+use crate::errors::report::{ErrorContext};
+use crate::tasks::clean::{maybe_clean_oc, maybe_clean_vsc, maybe_clean_se};
+use crate::tasks::purge::{schedule_purge, cancel_purge, resume_purges};
+use crate::tasks::event::{schedule_event_room, schedule_event_rooms};
+use crate::tasks::archive::{transcripts_enabled};
+use crate::utils::command_router::{handle_command_interaction, check_command_cooldown, command_path, is_ephemeral_command};
+use crate::commands::build::{INVITE_OPTIONS};
+use crate::commands::invite::{INVITE_COMMAND_NAME};
 use crate::utils::discord_display::DiscordDisplay;
+use crate::utils::moderation::{moderate_rename};
 use crate::database::models::{connect as db_connect};
 
 
@@ -35,7 +44,8 @@ struct BobHandler;
 
 impl BobHandler {
     async fn register_commands(&self, ctx: &Context) -> BobResult<()> {
-        Command::create_global_application_command(&ctx.http, |c| c
+        Command::create_global_application_command(&ctx.http, |c| {
+            c
             .name("build")
             .description("Build a new temporary channel.")
             .create_option(|o| o
@@ -58,7 +68,37 @@ impl BobHandler {
                 .add_string_choice("Voice", "Voice")
                 .add_string_choice("Stage", "Stage")
             )
-        ).await.bob_catch(ErrorKind::Admin, "Couldn't create global command")?;
+            .create_option(|o| o
+                .kind(CommandOptionType::Boolean)
+                .name("bare")
+                .description("Build the channel without any preset, ignoring the default one.")
+                .required(false)
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::Channel)
+                .name("category")
+                .description("The category to build the channel in, instead of the one of this channel.")
+                .required(false)
+                .channel_types(&[ChannelType::Category])
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::Boolean)
+                .name("private")
+                .description("Prevent everyone but you and the invited members and roles from joining the channel.")
+                .required(false)
+            );
+
+            for option in INVITE_OPTIONS {
+                c.create_option(|o| o
+                    .kind(CommandOptionType::Mentionable)
+                    .name(option)
+                    .description("A member or a role to allow in the channel.")
+                    .required(false)
+                );
+            }
+
+            c
+        }).await.bob_catch(ErrorKind::Admin, "Couldn't create global command")?;
 
         Command::create_global_application_command(&ctx.http, |c| c
             .name("save")
@@ -118,6 +158,447 @@ impl BobHandler {
                     .add_int_choice("6 hours", 21600)
                 )
             )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("preset")
+                .description("Set the preset used by /build when no preset is specified.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The default preset, or nothing to unset it.")
+                    .required(false)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("category")
+                    .description("The category to set the default preset of, or nothing for the whole server.")
+                    .required(false)
+                    .channel_types(&[ChannelType::Category])
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("limits")
+                .description("Limit the number of temporary channels which can exist at the same time.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Integer)
+                    .name("member")
+                    .description("How many channels each member can own, or 0 for no limit.")
+                    .required(false)
+                    .min_int_value(0)
+                    .max_int_value(50)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Integer)
+                    .name("category")
+                    .description("How many channels can be built in each category, or 0 for no limit.")
+                    .required(false)
+                    .min_int_value(0)
+                    .max_int_value(50)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Integer)
+                    .name("server")
+                    .description("How many channels can be built in the whole server, or 0 for no limit.")
+                    .required(false)
+                    .min_int_value(0)
+                    .max_int_value(500)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("builders")
+                .description("Choose the roles whose members can build channels.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("action")
+                    .description("Whether to add or remove a role, or to let everyone build channels.")
+                    .required(true)
+                    .add_string_choice("Add a role", "add")
+                    .add_string_choice("Remove a role", "remove")
+                    .add_string_choice("Let everyone build", "everyone")
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Role)
+                    .name("role")
+                    .description("The role to add or remove.")
+                    .required(false)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("categories")
+                .description("Choose the categories where /build can be used.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("category")
+                    .description("The category to allow or disallow.")
+                    .required(true)
+                    .channel_types(&[ChannelType::Category])
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Boolean)
+                    .name("allowed")
+                    .description("Should channels be buildable in the category?")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("overflow")
+                .description("Set the category where channels should be built when a category is full.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("category")
+                    .description("The category to set the overflow category of.")
+                    .required(true)
+                    .channel_types(&[ChannelType::Category])
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("overflow")
+                    .description("The category to build channels in when the first one is full, or nothing to unset it.")
+                    .required(false)
+                    .channel_types(&[ChannelType::Category])
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("order")
+                .description("Choose how temporary channels should be sorted in a category.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("category")
+                    .description("The category to sort the channels of.")
+                    .required(true)
+                    .channel_types(&[ChannelType::Category])
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("ordering")
+                    .description("How to sort the channels, or nothing to stop sorting them.")
+                    .required(false)
+                    .add_string_choice("Below an anchor channel", "anchor")
+                    .add_string_choice("Alphabetically", "alphabetical")
+                    .add_string_choice("By creation time", "creation")
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("anchor")
+                    .description("The channel to place temporary channels below, if sorting below an anchor channel.")
+                    .required(false)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("cooldown")
+                .description("Exempt the members of a role from the cooldowns of /build and /save.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Role)
+                    .name("role")
+                    .description("The role to exempt.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Boolean)
+                    .name("exempt")
+                    .description("Should the members of the role be exempt from cooldowns?")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("visibility")
+                .description("Choose whether the responses to a command should be visible to everyone.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("command")
+                    .description("The command to change the visibility of the responses of.")
+                    .required(true)
+                    .add_string_choice("/build", "build")
+                    .add_string_choice("/save", "save")
+                    .add_string_choice("/preset", "preset")
+                    .add_string_choice("/config", "config")
+                    .add_string_choice(INVITE_COMMAND_NAME, INVITE_COMMAND_NAME)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Boolean)
+                    .name("ephemeral")
+                    .description("Should responses only be visible to whoever used the command? Omit to use the default.")
+                    .required(false)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("archive")
+                .description("Set the channel where transcripts of the deleted channels should be sent.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("channel")
+                    .description("The text channel where transcripts should be sent, or nothing to stop sending them.")
+                    .required(false)
+                    .channel_types(&[ChannelType::Text])
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("companion")
+                .description("Build a text channel or a thread alongside each temporary channel.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("mode")
+                    .description("What to build alongside each channel, or nothing to stop building companions.")
+                    .required(false)
+                    .add_string_choice("Text channel", "channel")
+                    .add_string_choice("Thread in the command channel", "thread")
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("events")
+                .description("Build a room for the scheduled events of this server shortly before they start.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The preset to build the rooms with, or nothing to stop building them.")
+                    .required(false)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Integer)
+                    .name("lead")
+                    .description("How long before the start of an event its room should be built.")
+                    .required(false)
+                    .add_int_choice("5 minutes", 300)
+                    .add_int_choice("10 minutes", 600)
+                    .add_int_choice("15 minutes", 900)
+                    .add_int_choice("30 minutes", 1800)
+                    .add_int_choice("1 hour", 3600)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("category")
+                    .description("The category to build the rooms in.")
+                    .required(false)
+                    .channel_types(&[ChannelType::Category])
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("doctor")
+                .description("Check the configuration of the server and my permissions for problems.")
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("export")
+                .description("Export the configuration and the presets of this server to a file.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("format")
+                    .description("The format of the file to create.")
+                    .required(false)
+                    .add_string_choice("JSON", "json")
+                    .add_string_choice("TOML", "toml")
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("import")
+                .description("Import the configuration and the presets from a file created with /config export.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Attachment)
+                    .name("file")
+                    .description("The file to import.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Boolean)
+                    .name("apply")
+                    .description("Apply the changes, instead of only showing them?")
+                    .required(false)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("ban")
+                .description("Ban a member from building channels.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::User)
+                    .name("member")
+                    .description("The member to ban.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Integer)
+                    .name("duration")
+                    .description("How long the ban should last, or nothing for a permanent ban.")
+                    .required(false)
+                    .add_int_choice("1 hour", 3600)
+                    .add_int_choice("1 day", 86400)
+                    .add_int_choice("1 week", 604800)
+                    .add_int_choice("30 days", 2592000)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("reason")
+                    .description("Why the member is banned, shown to them when they try to build a channel.")
+                    .required(false)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("unban")
+                .description("Allow a banned member to build channels again.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::User)
+                    .name("member")
+                    .description("The member to unban.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("blocklist")
+                .description("Block or unblock a pattern in the names of the built channels.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("action")
+                    .description("Whether to block or unblock the pattern.")
+                    .required(true)
+                    .add_string_choice("Block", "add")
+                    .add_string_choice("Unblock", "remove")
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("pattern")
+                    .description("A word or a regular expression, matched case-insensitively.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("modlog")
+                .description("Set the channel where moderation actions are logged.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Channel)
+                    .name("channel")
+                    .description("The channel to log moderation actions in, or nothing to stop logging them.")
+                    .required(false)
+                    .channel_types(&[ChannelType::Text])
+                )
+            )
+        ).await.bob_catch(ErrorKind::Admin, "Couldn't create global command")?;
+
+        Command::create_global_application_command(&ctx.http, |c| c
+            .name("preset")
+            .description("Manage the presets of this server.")
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("info")
+                .description("Show who created a preset and who can use it.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("delete")
+                .description("Delete a preset.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset to delete.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("lock")
+                .description("Prevent everyone but admins from changing a preset.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset to lock or unlock.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Boolean)
+                    .name("locked")
+                    .description("Should the preset be locked?")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("restrict")
+                .description("Allow a role to use a preset, preventing everyone else from using it.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset to restrict.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Role)
+                    .name("role")
+                    .description("The role to allow.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("unrestrict")
+                .description("Remove a role from the ones allowed to use a preset.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Role)
+                    .name("role")
+                    .description("The role to remove.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("history")
+                .description("List the previous revisions of a preset.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset.")
+                    .required(true)
+                )
+            )
+            .create_option(|o| o
+                .kind(CommandOptionType::SubCommand)
+                .name("rollback")
+                .description("Restore a previous revision of a preset.")
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::String)
+                    .name("preset")
+                    .description("The name of the preset to restore.")
+                    .required(true)
+                )
+                .create_sub_option(|so| so
+                    .kind(CommandOptionType::Integer)
+                    .name("revision")
+                    .description("The number of the revision to restore, as shown by /preset history.")
+                    .required(true)
+                    .min_int_value(1)
+                )
+            )
+        ).await.bob_catch(ErrorKind::Admin, "Couldn't create global command")?;
+
+        Command::create_global_application_command(&ctx.http, |c| c
+            .name(INVITE_COMMAND_NAME)
+            .kind(CommandType::User)
         ).await.bob_catch(ErrorKind::Admin, "Couldn't create global command")?;
 
         Ok(())
@@ -134,12 +615,74 @@ impl EventHandler for BobHandler {
         }
     }
 
+    /// Called when the bot joins a guild, or when a guild becomes available.
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: bool) {
+        debug!("Received event: guild_create");
+        if let Err(e) = cancel_purge(guild.id) {
+            warn!("{}", e)
+        }
+
+        if let Err(e) = schedule_event_rooms(&ctx, guild.id).await {
+            warn!("{}", e)
+        }
+    }
+
+    /// Called when the bot is removed from a guild, or when a guild becomes unavailable.
+    async fn guild_delete(&self, ctx: Context, incomplete: UnavailableGuild, _full: Option<Guild>) {
+        debug!("Received event: guild_delete");
+
+        // The guild is having an outage, the bot hasn't been removed from it
+        if incomplete.unavailable {
+            return;
+        }
+
+        if let Err(e) = schedule_purge(ctx.http.clone(), incomplete.id) {
+            warn!("{}", e)
+        }
+    }
+
+    /// Called when a scheduled event is created.
+    async fn guild_scheduled_event_create(&self, ctx: Context, event: ScheduledEvent) {
+        debug!("Received event: guild_scheduled_event_create");
+        if let Err(e) = schedule_event_room(&ctx, &event) {
+            warn!("{}", e)
+        }
+    }
+
+    /// Called when a scheduled event is edited, started or ended.
+    async fn guild_scheduled_event_update(&self, ctx: Context, event: ScheduledEvent) {
+        debug!("Received event: guild_scheduled_event_update");
+        if let Err(e) = schedule_event_room(&ctx, &event) {
+            warn!("{}", e)
+        }
+
+        if let Err(e) = maybe_clean_se(&ctx, &event).await {
+            warn!("{}", e)
+        }
+    }
+
+    /// Called when a scheduled event is deleted.
+    async fn guild_scheduled_event_delete(&self, ctx: Context, mut event: ScheduledEvent) {
+        debug!("Received event: guild_scheduled_event_delete");
+
+        // Deleted events are reported with their last status
+        event.status = ScheduledEventStatus::Canceled;
+
+        if let Err(e) = maybe_clean_se(&ctx, &event).await {
+            warn!("{}", e)
+        }
+    }
+
     /// Handle the ready event.
     async fn ready(&self, ctx: Context, ready: Ready) {
         debug!("Received event: ready");
 
         info!("{} is ready!", &ready.user.name);
 
+        if let Err(e) = resume_purges(ctx.http.clone(), ready.shard) {
+            warn!("Failed to resume pending purges: {}", &e)
+        }
+
         let register_commands = env::var("DISCORD_REGISTER_COMMANDS").is_ok();
         match register_commands {
             true => {
@@ -170,15 +713,56 @@ impl EventHandler for BobHandler {
         };
     }
 
+    /// Called when a channel is updated.
+    async fn channel_update(&self, ctx: Context, old: Option<Channel>, new: Channel) {
+        debug!("Received event: channel_update");
+
+        if let Err(e) = moderate_rename(&ctx, &old, &new).await {
+            warn!("{}", e)
+        };
+    }
+
     /// Called when a new interaction is started.
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         debug!("Received event: interaction_create | {:?}", &interaction);
 
         match &interaction {
             Interaction::ApplicationCommand(command) => {
+                match check_command_cooldown(command) {
+                    Ok(None) => {},
+                    Ok(Some(wait)) => {
+                        let available_at = (SystemTime::now() + wait)
+                            .duration_since(UNIX_EPOCH)
+                            .map_or(0, |d| d.as_secs() + 1);
+
+                        let result = command.create_interaction_response(&ctx.http, |r| r
+                            .kind(InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|d| d
+                                .content(format!("🕒 Slow down! You'll be able to use this command again <t:{}:R>.", available_at))
+                                .ephemeral(true)
+                            )
+                        ).await;
+
+                        if let Err(err) = result {
+                            warn!("Could not respond to interaction: {:?}", &err);
+                        }
+                        return;
+                    },
+                    Err(e) => warn!("Could not check cooldowns: {}", &e),
+                }
+
+                let ephemeral = match is_ephemeral_command(command) {
+                    Ok(ephemeral) => ephemeral,
+                    Err(e) => {
+                        warn!("Could not check command visibility: {}", &e);
+                        false
+                    },
+                };
+
                 // Respond early, as not all commands may complete in less than 3 seconds
                 let result = command.create_interaction_response(&ctx.http, |r| r
                     .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|d| d.ephemeral(ephemeral))
                 ).await;
 
                 if let Err(err) = result {
@@ -186,11 +770,50 @@ impl EventHandler for BobHandler {
                     return;
                 }
 
-                let content = match handle_command_interaction(&ctx, command).await {
-                    Ok(s) => s,
-                    Err(e) => e.to_discord(),
+                let (content, failed) = match handle_command_interaction(&ctx, command).await {
+                    Ok(s) => (s, false),
+                    Err(e) => {
+                        let context = ErrorContext {
+                            correlation_id: command.id.to_string(),
+                            guild_id: command.guild_id,
+                            command: Some(command_path(&command.data)),
+                        };
+
+                        let content = match e.is_reportable() {
+                            true => {
+                                error!("{} | Error id: {}", &e, &context.correlation_id);
+                                if let Err(re) = e.report(&ctx.http, &context).await {
+                                    warn!("Could not report error {}: {}", &context.correlation_id, &re);
+                                }
+                                format!("{}Error id: `{}`", e.to_discord(), &context.correlation_id)
+                            },
+                            false => {
+                                e.log();
+                                e.to_discord()
+                            },
+                        };
+
+                        (content, true)
+                    },
                 };
 
+                // The deferred response can't be made ephemeral anymore, so errors are sent in a follow-up instead
+                if failed && !ephemeral {
+                    if let Err(err) = command.delete_original_interaction_response(&ctx.http).await {
+                        warn!("Could not delete interaction response: {:?}", &err);
+                    }
+
+                    let result = command.create_followup_message(&ctx.http, |m| m
+                        .content(content)
+                        .ephemeral(true)
+                    ).await;
+
+                    if let Err(err) = result {
+                        warn!("Could not send interaction follow-up: {:?}", &err);
+                    }
+                    return;
+                }
+
                 let result = command.edit_original_interaction_response(&ctx.http, |r| r
                     .content(content)
                 ).await;
@@ -235,9 +858,24 @@ async fn main() {
     }
     info!("Successfully ran all migrations.");
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        debug!("Running command line command: {:?}", &args);
+        if let Err(e) = cli::run(&token, &args).await {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_VOICE_STATES | GatewayIntents::GUILD_SCHEDULED_EVENTS;
+    if transcripts_enabled() {
+        info!("Requesting the Message Content intent, BOB_TRANSCRIPTS is set.");
+        intents |= GatewayIntents::MESSAGE_CONTENT;
+    }
 
     debug!("Building client...");
-    let mut client = Client::builder(&token, GatewayIntents::GUILDS | GatewayIntents::GUILD_VOICE_STATES)
+    let mut client = Client::builder(&token, intents)
         .event_handler(BobHandler)
         .application_id(appid)
         .await
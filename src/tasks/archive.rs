@@ -0,0 +1,127 @@
+//! This module contains a task to archive the text chat of a channel before it is deleted.
+
+use std::env;
+use serenity::model::prelude::*;
+use serenity::prelude::{Context};
+use crate::errors::*;
+use crate::database::models::{WithArchiveChannel, MayHaveBeenCreatedByBob};
+
+
+/// The maximum number of messages included in a transcript; older messages are left out.
+const MAX_TRANSCRIPT_MESSAGES: usize = 5000;
+
+
+/// Check whether transcripts are enabled on this instance, through the `BOB_TRANSCRIPTS` environment variable.
+///
+/// Transcripts require the privileged **Message Content** intent, which is only requested if they are enabled.
+pub fn transcripts_enabled() -> bool {
+    env::var("BOB_TRANSCRIPTS").is_ok()
+}
+
+
+/// Retrieve the most recent messages sent in the given [ChannelId], from the oldest to the newest.
+async fn collect_messages(ctx: &Context, channel_id: ChannelId) -> BobResult<Vec<Message>> {
+    let mut messages: Vec<Message> = vec![];
+
+    while messages.len() < MAX_TRANSCRIPT_MESSAGES {
+        let before = messages.last().map(|m| m.id);
+        let page = channel_id.messages(&ctx.http, |r| {
+            if let Some(before) = before {
+                r.before(before);
+            }
+            r.limit(100)
+        }).await.bob_catch(ErrorKind::Admin, "Couldn't retrieve the messages of the channel.")?;
+
+        let done = page.len() < 100;
+        messages.extend(page);
+
+        if done {
+            break;
+        }
+    }
+
+    messages.truncate(MAX_TRANSCRIPT_MESSAGES);
+    messages.reverse();
+    Ok(messages)
+}
+
+
+/// Render a plain text transcript of the given [Message]s.
+fn render_transcript(channel: &GuildChannel, owner: &str, deleted_at: Timestamp, messages: &[Message]) -> String {
+    let mut lines = vec![
+        format!("Room: #{}", &channel.name),
+        format!("Owner: {}", owner),
+        format!("Created: {}", channel.id.created_at()),
+        format!("Deleted: {}", deleted_at),
+        String::new(),
+    ];
+
+    for message in messages {
+        lines.push(format!("[{}] {}: {}", &message.timestamp, &message.author.tag(), &message.content));
+        for attachment in message.attachments.iter() {
+            lines.push(format!("    📎 {}", &attachment.url));
+        }
+    }
+
+    lines.join("\n")
+}
+
+
+/// If transcripts are enabled and an archive channel has been set in the guild, send a transcript of the text chat of
+/// the given [GuildChannel] to the archive channel.
+///
+/// _To be run right before the channel is deleted._
+///
+/// # Returns
+///
+/// - `Ok(Some(msg))` if a transcript was sent.
+/// - `Ok(None)` if transcripts are disabled, or if nothing was sent in the channel.
+/// - `Err(_)` if an error occurred.
+pub async fn task_archive(ctx: &Context, channel: &GuildChannel) -> BobResult<Option<Message>> {
+    debug!("Running task: archive | #{}", &channel.name);
+
+    if !transcripts_enabled() {
+        return Ok(None);
+    }
+
+    let archive = match channel.guild_id.get_archive_channel()? {
+        Some(archive) => archive,
+        None => return Ok(None),
+    };
+
+    let messages = collect_messages(ctx, channel.id).await?;
+    if messages.is_empty() {
+        return Ok(None);
+    }
+
+    let owner = channel.get_owner()?;
+    let owner_tag = match owner {
+        Some(owner) => match owner.to_user(&ctx.http).await {
+            Ok(user) => user.tag(),
+            Err(_) => owner.to_string(),
+        },
+        None => "unknown".to_string(),
+    };
+    let deleted_at = Timestamp::now();
+
+    let transcript = render_transcript(channel, &owner_tag, deleted_at, &messages);
+
+    let message = archive.send_files(
+        &ctx.http,
+        vec![AttachmentType::Bytes {
+            data: transcript.into_bytes().into(),
+            filename: format!("transcript-{}-{}.txt", &channel.name, &channel.id),
+        }],
+        |m| m
+            .content(format!(
+                "🗄 Transcript of _#{}_, built by {}, created <t:{}:f> and deleted <t:{}:f>.",
+                &channel.name,
+                owner.map_or_else(|| "_unknown_".to_string(), |o| o.mention().to_string()),
+                channel.id.created_at().unix_timestamp(),
+                deleted_at.unix_timestamp(),
+            ))
+            .allowed_mentions(|am| am.empty_parse())
+    ).await.bob_catch(ErrorKind::Admin, "Couldn't send the transcript to the archive channel.")?;
+
+    Ok(Some(message))
+}
@@ -1,47 +1,281 @@
 //! This module contains a task to build a new channel.
 
+use std::collections::{HashSet};
+use std::convert::{TryFrom};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 use crate::errors::*;
-use crate::database::models::{MayHaveBeenCreatedByBob, CanGetPresetData};
-use crate::utils::permission_overwrites::ChannelBuilderPermissionOverwrites;
+use crate::database::models::{MayHaveBeenCreatedByBob, CanGetPresetData, WithDefaultPreset, WithCompanionMode, WithCommandChannel, CompanionMode, WithRoomLimits, WithCreatedChannels, WithOverflowCategories, WithBuilderRoles};
+use crate::tasks::order::{task_order};
+use crate::utils::permission_overwrites::{ChannelBuilderPermissionOverwrites, RoomAccess};
+use crate::utils::moderation::{moderate_build};
+
+
+/// The maximum number of channels Discord allows in a single category.
+const CATEGORY_CHANNEL_LIMIT: usize = 50;
+
+
+/// The preset to build a channel with.
+#[derive(Debug, Clone, Copy)]
+pub enum BuildPreset<'a> {
+    /// Use the default preset of the category, or of the guild if the category doesn't have one.
+    ///
+    /// If the creator isn't allowed to use the default preset, no preset is used.
+    Default,
+    /// Use the preset with the given name.
+    Named(&'a str),
+    /// Don't use any preset, not even the default one.
+    Bare,
+}
+
+
+/// How a channel should be built by [task_build].
+#[derive(Debug, Clone)]
+pub struct BuildOptions<'a> {
+    /// The kind of the channel, overriding the one of the preset.
+    pub kind: Option<ChannelType>,
+    /// The preset to use as a template for the channel.
+    pub preset: BuildPreset<'a>,
+    /// Who should be able to join the channel.
+    pub access: RoomAccess,
+    /// The scheduled event the channel is the room of, if any.
+    pub event: Option<ScheduledEventId>,
+}
+
+impl Default for BuildOptions<'_> {
+    fn default() -> Self {
+        BuildOptions {
+            kind: None,
+            preset: BuildPreset::Default,
+            access: RoomAccess::default(),
+            event: None,
+        }
+    }
+}
+
+
+/// Ensure that the given [Member] has one of the builder roles of the guild, if any has been set.
+///
+/// It's checked here rather than in the commands, so that it applies to every way a room can be built.
+fn check_builder_roles(guild_id: GuildId, member: &Member) -> BobResult<()> {
+    let roles = guild_id.get_builder_roles()?;
+    if roles.is_empty() || member.roles.iter().any(|r| roles.contains(r)) {
+        return Ok(());
+    }
+
+    let roles: Vec<String> = roles.iter().map(|r| r.mention().to_string()).collect();
+    Err(BobError::from_msg(ErrorKind::User, &format!(
+        "You need one of these roles to build channels: {}.", roles.join(", ")
+    )))
+}
+
+
+/// Ensure that building a new channel for the `creator` in the given `category` wouldn't exceed the [RoomLimits] of the
+/// guild.
+///
+/// Channels which were deleted without Bob noticing are forgotten along the way.
+///
+/// [RoomLimits]: crate::database::models::RoomLimits
+async fn check_room_limits(ctx: &Context, guild: &PartialGuild, creator: &Member, category: &Option<ChannelCategory>) -> BobResult<()> {
+    let limits = match guild.id.get_room_limits()? {
+        Some(limits) => limits,
+        None => return Ok(()),
+    };
+
+    let channels = guild.id.channels(&ctx.http)
+        .await.bob_catch(ErrorKind::External, "Couldn't retrieve the channels of the server.")?;
+
+    // Companions are left out, as they aren't rooms, and threads aren't returned by Discord along with the channels
+    let mut rooms: Vec<(&GuildChannel, Option<UserId>)> = vec![];
+    for (channel_id, owner) in guild.id.get_created_rooms()? {
+        match channels.get(&channel_id) {
+            None => guild.id.forget_created_channel(channel_id)?,
+            Some(channel) if channel.kind == ChannelType::Voice || channel.kind == ChannelType::Stage => rooms.push((channel, owner)),
+            Some(_) => {},
+        }
+    }
+
+    let owned: Vec<String> = rooms.iter()
+        .filter(|(_, owner)| owner == &Some(creator.user.id))
+        .map(|(channel, _)| channel.mention().to_string())
+        .collect();
+    let owned_note = match owned.is_empty() {
+        true => "".to_string(),
+        false => format!(" You already own {}.", owned.join(", ")),
+    };
+
+    if let Some(limit) = limits.member_limit() {
+        if owned.len() >= limit {
+            return Err(BobError::from_msg(ErrorKind::User, &format!(
+                "You can't own more than **{}** temporary channels at once.{}", limit, &owned_note
+            )))
+        }
+    }
+
+    if let (Some(limit), Some(category)) = (limits.category_limit(), category) {
+        let in_category = rooms.iter().filter(|(channel, _)| channel.parent_id == Some(category.id)).count();
+        if in_category >= limit {
+            return Err(BobError::from_msg(ErrorKind::User, &format!(
+                "There can't be more than **{}** temporary channels in **{}**.{}", limit, &category.name, &owned_note
+            )))
+        }
+    }
+
+    if let Some(limit) = limits.guild_limit() {
+        if rooms.len() >= limit {
+            return Err(BobError::from_msg(ErrorKind::User, &format!(
+                "There can't be more than **{}** temporary channels in this server.{}", limit, &owned_note
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+
+/// A channel built by [task_build].
+#[derive(Debug, Clone)]
+pub struct BuiltChannel {
+    pub channel: GuildChannel,
+    /// The [Permissions] which couldn't be granted to the creator or put in the other overwrites, as Bob doesn't have
+    /// them.
+    pub ungranted: Permissions,
+}
+
+
+/// Find the category where a channel meant for the given `category` should be placed.
+///
+/// If the category is full and an overflow category has been set for it, the chain of overflow categories is followed
+/// until a category with some room left is found.
+///
+/// # Returns
+///
+/// - `Ok(Some(category_id))` with the category where the channel should be placed.
+/// - `Ok(None)` if no category was specified.
+/// - `Err(_)` if every category in the chain is full, or if an error occurred.
+async fn find_placement(ctx: &Context, guild: &PartialGuild, category: &Option<ChannelCategory>) -> BobResult<Option<ChannelId>> {
+    let category = match category {
+        Some(category) => category,
+        None => return Ok(None),
+    };
+
+    if guild.id.get_overflow_category(category.id)?.is_none() {
+        return Ok(Some(category.id));
+    }
+
+    let channels = guild.id.channels(&ctx.http)
+        .await.bob_catch(ErrorKind::External, "Couldn't retrieve the channels of the server.")?;
+
+    let mut visited: HashSet<ChannelId> = HashSet::new();
+    let mut current = Some(category.id);
+
+    while let Some(category_id) = current {
+        if !visited.insert(category_id) {
+            break;
+        }
+
+        // Overflow categories which were deleted are skipped
+        if channels.contains_key(&category_id) {
+            let count = channels.values().filter(|c| c.parent_id == Some(category_id)).count();
+            if count < CATEGORY_CHANNEL_LIMIT {
+                return Ok(Some(category_id));
+            }
+        }
+
+        current = guild.id.get_overflow_category(category_id)?;
+    }
+
+    Err(BobError::from_msg(ErrorKind::User, &format!(
+        "**{}** and all of its overflow categories are full.", &category.name
+    )))
+}
 
 
 /// Build a new channel in the specified [`guild`]([Guild]) with the specified `name`.
 ///
-/// The function optionally accepts a [`category`]([ChannelCategory]) and a `preset_name`:
-/// - if a `category` is specified, the channel is created in it and inherits its [PermissionOverwrite]s.
-/// - if a `preset_name` is specified, the preset with that name is loaded and used as a template for the channel,
+/// The function optionally accepts a [`category`]([ChannelCategory]) and a [`preset`](BuildPreset) in its
+/// [BuildOptions]:
+/// - if a `category` is specified, the channel is created in it and inherits its [PermissionOverwrite]s; if the category
+///   is full, the channel is created in its overflow category instead, still inheriting the [PermissionOverwrite]s of
+///   the original one.
+/// - if a `preset` is specified or a default one is set, the preset is loaded and used as a template for the channel,
 ///   inheriting the following properties:
 ///     - [PermissionOverwrite]s
+///     - Channel kind, if `kind` is not specified (defaulting to [ChannelType::Voice])
 ///     - Bitrate (defaulting to 64 kbps)
 ///     - User limit (defaulting to None)
+///     - Video quality mode and RTC region (defaulting to automatic)
+///     - NSFW flag and topic
 ///
-/// # Returns
+/// The [`access`](RoomAccess) can make the channel private, and allow specific members and roles to join it.
 ///
-/// - `Ok(msg)` if the channel creation was successful.
-/// - `Err(_)` if something went wrong in the creation of the channel.
+/// If a [CompanionMode] is set for the guild, a companion text channel or thread is built too; if it can't be built,
+/// the channel is deleted again.
 ///
-/// # To do
+/// Nothing is built if the `creator` doesn't have one of the builder roles of the guild, if they're banned from building
+/// channels, if the `name` matches the name blocklist, or if the limits on the number of channels set for the guild
+/// would be exceeded.
 ///
-/// Presets aren't loaded yet.
+/// Once built, the channel is moved according to the [RoomOrdering] of its category.
 ///
-pub async fn task_build(ctx: &Context, guild: &PartialGuild, name: &str, kind: ChannelType, creator: &Member, category: &Option<ChannelCategory>, preset: &Option<&str>) -> BobResult<GuildChannel> {
+/// If Bob isn't an Administrator, the creator is only granted the voice permissions Bob has in the category the channel
+/// is placed in, and the other overwrites only keep the permissions Bob has there; the others are reported in the
+/// [BuiltChannel].
+///
+/// # Returns
+///
+/// - `Ok(built)` if the channel creation was successful.
+/// - `Err(_)` if something went wrong in the creation of the channel.
+///
+/// [RoomOrdering]: crate::database::models::RoomOrdering
+pub async fn task_build(ctx: &Context, guild: &PartialGuild, name: &str, creator: &Member, category: &Option<ChannelCategory>, options: &BuildOptions<'_>) -> BobResult<BuiltChannel> {
     debug!(
-        "Running task: build | In <G:{}>, build #{} in <C:{}> with preset {}",
+        "Running task: build | In <G:{}>, build #{} in <C:{}> with {:?}",
         &guild.name,
         &name,
         &category.as_ref().map_or_else(|| "<no category>", |ok| ok.name()),
-        (*preset).map_or_else(|| "<no preset>".to_string(), |ok| format!("'{}'", ok))
+        &options,
     );
 
+    check_builder_roles(guild.id, creator)?;
+    moderate_build(ctx, guild.id, creator, name).await?;
+    check_room_limits(ctx, guild, creator, category).await?;
+    let parent = find_placement(ctx, guild, category).await?;
+
+    let preset = match options.preset {
+        BuildPreset::Named(preset) => Some(preset.to_string()),
+        BuildPreset::Default => guild.id.find_default_preset(category.as_ref().map(|c| c.id))?,
+        BuildPreset::Bare => None,
+    };
+
     let preset = match preset {
-        Some(preset) => Some(guild.id.get_preset_data(preset)?.bob_catch(ErrorKind::User, "No such preset.")?),
+        Some(preset) => {
+            let preset = guild.id.get_preset(&preset)?.bob_catch(ErrorKind::User, "No such preset.")?;
+            match (preset.check_use(creator), options.preset) {
+                (Ok(_), _) => Some(preset.data()?),
+                // Members who can't use the default preset still get a bare channel
+                (Err(_), BuildPreset::Default) => {
+                    debug!("<U:{}> can't use default preset {}, building a bare channel", &creator.user.id, &preset.preset_name);
+                    None
+                },
+                (Err(e), _) => return Err(e),
+            }
+        },
         None => None
     };
-    let permissions = ChannelBuilderPermissionOverwrites::fetch(ctx, creator, category, preset).await?;
-    let bitrate: Option<u32> = None;
-    let limit: Option<u32> = None;
+    let permissions = ChannelBuilderPermissionOverwrites::fetch(ctx, guild, creator, category, parent, &preset, &options.access).await?;
+
+    let kind = options.kind
+        .or_else(|| preset.as_ref().and_then(|p| p.kind))
+        .unwrap_or(ChannelType::Voice);
+    let bitrate: Option<u32> = match &preset {
+        Some(p) => Some(u32::try_from(p.bitrate).bob_catch(ErrorKind::Developer, "Bitrate was somehow larger than a u32")?),
+        None => None,
+    };
+    let limit: Option<u32> = match preset.as_ref().and_then(|p| p.user_limit) {
+        Some(l) => Some(u32::try_from(l).bob_catch(ErrorKind::Developer, "User limit was somehow larger than a u32")?),
+        None => None,
+    };
 
     match kind {
         ChannelType::Voice | ChannelType::Stage => {}
@@ -52,23 +286,113 @@ pub async fn task_build(ctx: &Context, guild: &PartialGuild, name: &str, kind: C
         }
     }
 
+    let ungranted = permissions.ungranted();
+    let permissions = permissions.merge();
+
     let created = guild.create_channel(&ctx.http, |c| {
         c.name(name);
         c.kind(kind);
-        if let Some(cat) = category {
-            c.category(cat.id);
+        if let Some(parent) = parent {
+            c.category(parent);
         }
 
-        c.permissions(permissions.merge());
+        c.permissions(permissions.clone());
         c.bitrate(bitrate.unwrap_or(64000));
         if let Some(limit) = limit {
             c.user_limit(limit);
         }
 
+        if let Some(preset) = &preset {
+            c.nsfw(preset.nsfw);
+            if let Some(topic) = &preset.topic {
+                c.topic(topic);
+            }
+            // Not supported by the CreateChannel builder yet
+            if let Some(region) = &preset.rtc_region {
+                c.0.insert("rtc_region", serde_json::Value::from(region.as_str()));
+            }
+            if let Some(quality) = preset.video_quality {
+                c.0.insert("video_quality_mode", serde_json::Value::from(quality as u8));
+            }
+        }
+
         c
     }).await.bob_catch(ErrorKind::Admin, "Failed to create channel")?;
 
-    created.mark_as_created_by_bob()?;
+    // Rooms of scheduled events are marked right away, so that they aren't cleaned before their event starts
+    created.mark_as_created_by_bob(creator.user.id, options.event)?;
+
+    if let Some(mode) = guild.id.get_companion_mode()? {
+        match build_companion(ctx, guild, &created, creator, parent, permissions, mode).await {
+            Ok(companion) => { created.mark_companion(companion.id)?; },
+            Err(e) => {
+                // Don't leave a room behind if the whole build failed
+                if let Err(de) = created.delete(&ctx.http).await {
+                    warn!("Couldn't delete #{} after its companion failed to build: {:?}", &created.name, &de);
+                }
+                guild.id.forget_created_channel(created.id)?;
+                return Err(e);
+            },
+        }
+    }
+
+    if let Err(e) = task_order(ctx, guild.id, parent).await {
+        warn!("Couldn't reorder the channels of <C:{:?}>: {}", &parent, &e);
+    }
+
+    if !ungranted.is_empty() {
+        warn!("Couldn't set {:?} in the overwrites of #{}", &ungranted, &created.name);
+    }
+
+    Ok(BuiltChannel { channel: created, ungranted })
+}
+
+
+/// Build the companion of the given `channel`, according to the given [CompanionMode]:
+/// - [CompanionMode::Channel] creates a text channel next to it with the same [PermissionOverwrite]s;
+/// - [CompanionMode::Thread] creates a private thread in the command channel of the guild, adding the `creator` to it.
+async fn build_companion(ctx: &Context, guild: &PartialGuild, channel: &GuildChannel, creator: &Member, parent: Option<ChannelId>, permissions: Vec<PermissionOverwrite>, mode: CompanionMode) -> BobResult<GuildChannel> {
+    debug!("Building {:?} companion of #{}", &mode, &channel.name);
+
+    let companion = match mode {
+        CompanionMode::Channel => {
+            guild.create_channel(&ctx.http, |c| {
+                c.name(&channel.name);
+                c.kind(ChannelType::Text);
+                if let Some(parent) = parent {
+                    c.category(parent);
+                }
+
+                c.permissions(permissions);
+
+                c
+            }).await.bob_catch(ErrorKind::Admin, "Failed to create companion channel")?
+        },
+        CompanionMode::Thread => {
+            let cc = guild.id.get_command_channel()?
+                .bob_catch(ErrorKind::Admin, "No command channel has been set in this Server.")?;
+
+            let thread = cc.create_private_thread(&ctx.http, |t| t
+                .name(&channel.name)
+                .kind(ChannelType::PrivateThread)
+            ).await.bob_catch(ErrorKind::Admin, "Failed to create companion thread")?;
+
+            let added = thread.id.add_thread_member(&ctx.http, creator.user.id)
+                .await.bob_catch(ErrorKind::Admin, "Couldn't add the creator to the companion thread");
+
+            if let Err(e) = added {
+                // The thread isn't tracked yet, so it would be left behind
+                if let Err(de) = thread.delete(&ctx.http).await {
+                    warn!("Couldn't delete companion thread #{} after failing to add its creator: {:?}", &thread.name, &de);
+                }
+                return Err(e);
+            }
+
+            thread
+        },
+    };
+
+    companion.mark_as_created_by_bob(creator.user.id, None)?;
 
-    Ok(created)
+    Ok(companion)
 }
\ No newline at end of file
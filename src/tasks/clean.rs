@@ -1,12 +1,15 @@
 //! This module contains a task to clear empty channels.
 
 use std::time::{SystemTime, Duration, UNIX_EPOCH};
-use serenity::model::prelude::{VoiceState, ChannelId, GuildChannel, Mentionable};
+use serenity::model::prelude::{VoiceState, ChannelId, ChannelType, GuildChannel, Mentionable, ScheduledEvent, ScheduledEventStatus};
 use serenity::prelude::{Context};
 use tokio::time::{sleep};
 use crate::errors::{BobResult, BobCatch, ErrorKind};
-use crate::database::models::{WithCommandChannel, WithDeletionTime, MayHaveBeenCreatedByBob};
+use crate::errors::discord::{json_error_code, UNKNOWN_SCHEDULED_EVENT};
+use crate::database::models::{WithCommandChannel, WithDeletionTime, MayHaveBeenCreatedByBob, WithEventRooms, WithCreatedChannels};
 use crate::extensions::*;
+use crate::tasks::archive::{task_archive};
+use crate::tasks::order::{task_order};
 
 
 /// _To be run in a `voice_state_change` event._
@@ -62,6 +65,45 @@ pub async fn maybe_clean_oc(
     Ok(result)
 }
 
+/// _To be run in a `guild_scheduled_event_update` or `guild_scheduled_event_delete` event._
+///
+/// Detect if a scheduled event with a room has ended, and run [task_clean] on its room.
+pub async fn maybe_clean_se(
+    ctx: &Context,
+    event: &ScheduledEvent,
+)
+    -> BobResult<Option<()>>
+{
+    if matches!(event.status, ScheduledEventStatus::Scheduled | ScheduledEventStatus::Active) {
+        return Ok(None);
+    }
+
+    match event.guild_id.get_event_room(event.id)? {
+        None => Ok(None),
+        Some(c) => {
+            let channel = &c.ext_guild_channel(&ctx.http).await?;
+
+            let result = task_clean(ctx, channel).await?.map(|_| ());
+            Ok(result)
+        },
+    }
+}
+
+/// Check whether the given [GuildChannel] is the room of a scheduled event which hasn't ended yet.
+async fn is_waiting_for_event(ctx: &Context, channel: &GuildChannel) -> BobResult<bool> {
+    let event_id = match channel.get_event()? {
+        Some(event_id) => event_id,
+        None => return Ok(false),
+    };
+
+    // Deleted events can't be retrieved anymore, but other errors don't tell anything about the event
+    match channel.guild_id.scheduled_event(&ctx.http, event_id, false).await {
+        Ok(event) => Ok(matches!(event.status, ScheduledEventStatus::Scheduled | ScheduledEventStatus::Active)),
+        Err(e) if json_error_code(&e) == Some(UNKNOWN_SCHEDULED_EVENT) => Ok(false),
+        Err(e) => Err(e).bob_catch(ErrorKind::External, "Couldn't retrieve the scheduled event of the room."),
+    }
+}
+
 /// Given two [VoiceState]s, determine if a channel was left and return its [ChannelId].
 async fn get_left_channel_id(old: &Option<VoiceState>, new: &VoiceState) -> Option<ChannelId> {
     let old_channel = old.as_ref().or(None)?.channel_id.or(None)?;
@@ -83,6 +125,13 @@ async fn get_left_channel_id(old: &Option<VoiceState>, new: &VoiceState) -> Opti
 /// If, at the end of the timeout, nobody is still inside the channel, delete it, then edit the previously sent
 /// notification.
 ///
+/// Rooms of scheduled events are never deleted before their event has ended.
+///
+/// If an archive channel has been set, a transcript of the text chat of the channel is sent there before deleting it.
+///
+/// Only voice and stage channels are cleaned: companion channels and threads are deleted together with the channel
+/// they were built for.
+///
 /// # Returns
 ///
 /// - `Err(e)` if an error is encountered while performing the action.
@@ -91,6 +140,10 @@ async fn get_left_channel_id(old: &Option<VoiceState>, new: &VoiceState) -> Opti
 pub async fn task_clean<'a>(ctx: &'_ Context, channel: &'a GuildChannel) -> BobResult<Option<&'a GuildChannel>> {
     debug!("Running task: clean | #{}", &channel.name);
 
+    if channel.kind != ChannelType::Voice && channel.kind != ChannelType::Stage {
+        return Ok(None);
+    }
+
     let gid = &channel.guild_id;
     let cc = gid.get_command_channel()?
         .bob_catch(ErrorKind::Admin, "No command channel has been set in this Server.")?;
@@ -100,6 +153,10 @@ pub async fn task_clean<'a>(ctx: &'_ Context, channel: &'a GuildChannel) -> BobR
         return Ok(None);
     }
 
+    if is_waiting_for_event(ctx, channel).await? {
+        return Ok(None);
+    }
+
     let time_current = SystemTime::now();
     let countdown = gid.get_deletion_time()?
         .unwrap_or(Duration::from_secs(60));
@@ -120,16 +177,33 @@ pub async fn task_clean<'a>(ctx: &'_ Context, channel: &'a GuildChannel) -> BobR
 
     let members_in_channel = channel.ext_members(&ctx.cache).await?;
 
-    if !members_in_channel.is_empty() {
+    // The channel may have become the room of an event, or its event may have been rescheduled, during the countdown
+    if !members_in_channel.is_empty() || is_waiting_for_event(ctx, channel).await? {
         message.delete(&ctx.http)
             .await.bob_catch(ErrorKind::Admin, "Couldn't delete countdown message.")?;
 
         return Ok(None);
     }
 
+    if let Err(e) = task_archive(ctx, channel).await {
+        warn!("Couldn't archive #{}: {}", &channel.name, &e);
+    }
+
     let _ = channel.delete(&ctx.http)
         .await.bob_catch(ErrorKind::Admin, "Couldn't delete channel.")?;
 
+    if let Some(companion) = channel.get_companion()? {
+        if let Err(e) = companion.delete(&ctx.http).await {
+            warn!("Couldn't delete companion of #{}: {:?}", &channel.name, &e);
+        }
+        gid.forget_created_channel(companion)?;
+    }
+    gid.forget_created_channel(channel.id)?;
+
+    if let Err(e) = task_order(ctx, *gid, channel.parent_id).await {
+        warn!("Couldn't reorder the channels of <C:{:?}>: {}", &channel.parent_id, &e);
+    }
+
     message.edit(
         &ctx.http,
         |m| m.content(
@@ -0,0 +1,179 @@
+//! This module contains a task to build the rooms of scheduled events.
+
+use std::collections::{HashSet};
+use std::sync::{Mutex};
+use std::time::{SystemTime, Duration, UNIX_EPOCH};
+use once_cell::sync::{Lazy};
+use serenity::model::prelude::*;
+use serenity::prelude::{Context};
+use tokio::time::{sleep};
+use crate::errors::*;
+use crate::extensions::*;
+use crate::database::models::{WithEventRooms, WithCommandChannel};
+use crate::tasks::build::{task_build, BuildPreset, BuildOptions};
+use crate::utils::channel_names::{Channelizable};
+
+
+/// The events which already have a [task_event_room] waiting for them, along with their start time.
+static PENDING_EVENTS: Lazy<Mutex<HashSet<(ScheduledEventId, i64)>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+
+/// _To be run in a `guild_scheduled_event_create` or `guild_scheduled_event_update` event._
+///
+/// If rooms should be built for the scheduled events of the guild, schedule a [task_event_room] for the given
+/// [ScheduledEvent].
+pub fn schedule_event_room(ctx: &Context, event: &ScheduledEvent) -> BobResult<()> {
+    if !matches!(event.status, ScheduledEventStatus::Scheduled) {
+        return Ok(());
+    }
+
+    if event.guild_id.get_event_room_config()?.is_none() {
+        return Ok(());
+    }
+
+    if event.guild_id.get_event_room(event.id)?.is_some() {
+        return Ok(());
+    }
+
+    let key = (event.id, event.start_time.unix_timestamp());
+    {
+        let mut pending = PENDING_EVENTS.lock().ok()
+            .bob_catch(ErrorKind::Developer, "Pending events lock was poisoned")?;
+
+        if !pending.insert(key) {
+            return Ok(());
+        }
+    }
+
+    let ctx = ctx.clone();
+    let guild_id = event.guild_id;
+    let event_id = event.id;
+    let start_time = event.start_time;
+
+    tokio::spawn(async move {
+        match task_event_room(&ctx, guild_id, event_id, start_time).await {
+            Ok(Some(channel)) => info!("Built room #{} for event <E:{}>", &channel.name, &event_id),
+            Ok(None) => debug!("Not building a room for event <E:{}>", &event_id),
+            Err(e) => warn!("{}", e),
+        }
+
+        if let Ok(mut pending) = PENDING_EVENTS.lock() {
+            pending.remove(&key);
+        }
+    });
+
+    Ok(())
+}
+
+
+/// _To be run in a `guild_create` event, or after changing the configuration of the guild._
+///
+/// Schedule a [task_event_room] for all the upcoming scheduled events of the given [GuildId].
+pub async fn schedule_event_rooms(ctx: &Context, guild_id: GuildId) -> BobResult<()> {
+    if guild_id.get_event_room_config()?.is_none() {
+        return Ok(());
+    }
+
+    let events = guild_id.scheduled_events(&ctx.http, false)
+        .await.bob_catch(ErrorKind::Admin, "Couldn't retrieve the scheduled events of the server.")?;
+
+    for event in events.iter() {
+        schedule_event_room(ctx, event)?;
+    }
+
+    Ok(())
+}
+
+
+/// Wait until shortly before `start_time`, then build a room for the given scheduled event with the preset set in the
+/// configuration of the guild, and point the location of the event at it.
+///
+/// Nothing is built if, in the meantime, the event was rescheduled, cancelled, or already got a room.
+///
+/// # Returns
+///
+/// - `Ok(Some(channel))` if the room was built.
+/// - `Ok(None)` if no room was needed.
+/// - `Err(_)` if an error occurred.
+pub async fn task_event_room(ctx: &Context, guild_id: GuildId, event_id: ScheduledEventId, start_time: Timestamp) -> BobResult<Option<GuildChannel>> {
+    debug!("Running task: event_room | <G:{}> <E:{}>", &guild_id, &event_id);
+
+    let config = match guild_id.get_event_room_config()? {
+        Some(config) => config,
+        None => return Ok(None),
+    };
+
+    let start_at = UNIX_EPOCH + Duration::from_secs(start_time.unix_timestamp().max(0).unsigned_abs());
+    let build_at = start_at.checked_sub(config.lead_time()?).unwrap_or(UNIX_EPOCH);
+    if let Ok(remaining) = build_at.duration_since(SystemTime::now()) {
+        sleep(remaining).await;
+    }
+
+    // The configuration may have been changed while waiting
+    let config = match guild_id.get_event_room_config()? {
+        Some(config) => config,
+        None => return Ok(None),
+    };
+
+    let event = guild_id.scheduled_event(&ctx.http, event_id, false)
+        .await.bob_catch(ErrorKind::External, "Couldn't retrieve the scheduled event.")?;
+
+    if !matches!(event.status, ScheduledEventStatus::Scheduled) || event.start_time != start_time {
+        return Ok(None);
+    }
+
+    if guild_id.get_event_room(event_id)?.is_some() {
+        return Ok(None);
+    }
+
+    let creator_id = event.creator_id
+        .bob_catch(ErrorKind::External, "Scheduled event has no creator")?;
+
+    let guild = guild_id.ext_partial_guild(&ctx.http).await?;
+    let creator = guild.ext_member(&ctx.http, creator_id).await?;
+
+    let category = match config.category()? {
+        Some(category_id) => Some(
+            category_id
+                .to_channel(&ctx.http)
+                .await
+                .bob_catch(ErrorKind::Admin, "Couldn't retrieve the category of event rooms.")?
+                .category()
+                .bob_catch(ErrorKind::Admin, "The category of event rooms isn't a category.")?
+        ),
+        None => None,
+    };
+
+    let kind = match event.kind {
+        ScheduledEventType::StageInstance => Some(ChannelType::Stage),
+        ScheduledEventType::Voice => Some(ChannelType::Voice),
+        _ => None,
+    };
+
+    let options = BuildOptions {
+        kind,
+        preset: BuildPreset::Named(&config.preset_name),
+        event: Some(event_id),
+        ..Default::default()
+    };
+
+    let created = task_build(ctx, &guild, &event.name.channelify(), &creator, &category, &options).await?.channel;
+
+    guild_id.edit_scheduled_event(&ctx.http, event_id, |e| {
+        match created.kind {
+            ChannelType::Stage => e.kind(ScheduledEventType::StageInstance),
+            _ => e.kind(ScheduledEventType::Voice),
+        };
+        e.channel_id(created.id);
+        e
+    }).await.bob_catch(ErrorKind::Admin, "Couldn't move the scheduled event to its room.")?;
+
+    if let Some(cc) = guild_id.get_command_channel()? {
+        let _ = cc.say(
+            &ctx.http,
+            format!("📅 Built {} for the event **{}**!", &created.mention(), &event.name)
+        ).await;
+    }
+
+    Ok(Some(created))
+}
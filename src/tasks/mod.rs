@@ -3,3 +3,7 @@
 pub mod clean;
 pub mod build;
 pub mod mov;
+pub mod purge;
+pub mod event;
+pub mod archive;
+pub mod order;
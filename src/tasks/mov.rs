@@ -12,13 +12,5 @@ pub async fn task_move(ctx: &Context, guild: &PartialGuild, user_id: UserId, cha
         &ctx.http,
         user_id,
         channel_id,
-    ).await.map_err(|err| {
-        // This is awful
-        if format!("{}", &err).contains("Target user is not connected to voice.") {
-            BobError::from_msg(ErrorKind::User, "You're not connected to voice chat!")
-        }
-        else {
-            BobError::from_msg(ErrorKind::Admin, "Could't move user to the newly created channel.")
-        }
-    })
-}
\ No newline at end of file
+    ).await.bob_catch(ErrorKind::Admin, "Couldn't move user to the newly created channel.")
+}
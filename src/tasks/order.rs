@@ -0,0 +1,151 @@
+//! This module contains a task to sort the channels built by Bob in a category.
+
+use std::collections::{HashSet};
+use serenity::model::prelude::*;
+use serenity::prelude::{Context};
+use crate::errors::*;
+use crate::database::models::{WithCategoryOrderings, WithCreatedChannels, RoomOrdering};
+
+
+/// Find the positions to give to the given `rooms`, in order, so that they are sorted right after the channel at
+/// `after` and right before the channel at `before`, without moving either.
+///
+/// Discord sorts channels by position and then by id, so rooms can share a position with their neighbours.
+///
+/// # Returns
+///
+/// - `Some(positions)` with the position of each room.
+/// - `None` if the rooms can't fit between the two channels without moving them.
+fn place_rooms(after: Option<(i64, ChannelId)>, before: Option<(i64, ChannelId)>, rooms: &[(i64, ChannelId)]) -> Option<Vec<i64>> {
+    // Without a channel before them, the rooms start from the topmost of their positions
+    let first = rooms.iter().map(|(position, _)| *position).min().unwrap_or(0);
+
+    let mut previous = after;
+    let mut positions = vec![];
+
+    for (_, room_id) in rooms.iter().copied() {
+        let position = match previous {
+            None => first,
+            Some((position, id)) if room_id > id => position,
+            Some((position, _)) => position + 1,
+        };
+        positions.push(position);
+        previous = Some((position, room_id));
+    }
+
+    match (previous, before) {
+        (Some(last), Some(before)) if last >= before => None,
+        _ => Some(positions),
+    }
+}
+
+
+/// Sort the voice and stage channels built by Bob in the given category, according to the [RoomOrdering] set for it.
+///
+/// Only the channels built by Bob are moved: they are placed between the channels which weren't built by Bob, which
+/// keep their positions.
+///
+/// _To be run after a channel is built in or deleted from the category._
+///
+/// # Returns
+///
+/// - `Ok(true)` if the channels were reordered.
+/// - `Ok(false)` if no [RoomOrdering] is set for the category, or if the channels were already in order.
+/// - `Err(_)` if the channels couldn't be sorted without moving the ones not built by Bob, or if an error occurred.
+pub async fn task_order(ctx: &Context, guild_id: GuildId, category_id: Option<ChannelId>) -> BobResult<bool> {
+    let category_id = match category_id {
+        Some(category_id) => category_id,
+        None => return Ok(false),
+    };
+
+    let config = match guild_id.get_category_ordering(category_id)? {
+        Some(config) => config,
+        None => return Ok(false),
+    };
+
+    debug!("Running task: order | <G:{}> <C:{}>", &guild_id, &category_id);
+
+    let ordering = config.ordering()?;
+    let anchor = config.anchor()?;
+
+    let created: HashSet<ChannelId> = guild_id.get_created_channels()?
+        .into_iter()
+        .map(|(channel_id, _)| channel_id)
+        .collect();
+
+    let channels = guild_id.channels(&ctx.http)
+        .await.bob_catch(ErrorKind::External, "Couldn't retrieve the channels of the server.")?;
+
+    let mut current: Vec<&GuildChannel> = channels.values()
+        .filter(|c| c.parent_id == Some(category_id))
+        .filter(|c| c.kind == ChannelType::Voice || c.kind == ChannelType::Stage)
+        .collect();
+    current.sort_by_key(|c| (c.position, c.id));
+
+    let (mut rooms, others): (Vec<&GuildChannel>, Vec<&GuildChannel>) = current.iter()
+        .partition(|c| created.contains(&c.id));
+
+    match ordering {
+        RoomOrdering::Alphabetical => rooms.sort_by_key(|c| (c.name.to_lowercase(), c.id)),
+        RoomOrdering::Anchor | RoomOrdering::Creation => rooms.sort_by_key(|c| c.id),
+    }
+
+    let insert_at = match (ordering, anchor) {
+        (RoomOrdering::Anchor, Some(anchor)) => others.iter()
+            .position(|c| c.id == anchor)
+            .map_or(others.len(), |i| i + 1),
+        _ => others.len(),
+    };
+    let after = insert_at.checked_sub(1).map(|i| (others[i].position, others[i].id));
+    let before = others.get(insert_at).map(|c| (c.position, c.id));
+    let placed: Vec<(i64, ChannelId)> = rooms.iter().map(|c| (c.position, c.id)).collect();
+
+    let positions = place_rooms(after, before, &placed)
+        .bob_catch(ErrorKind::Admin, "There's no space for the temporary channels below the anchor without moving other channels.")?;
+
+    let changes: Vec<(ChannelId, u64)> = rooms.iter()
+        .zip(positions)
+        .filter(|(c, position)| c.position != *position)
+        .map(|(c, position)| (c.id, position.max(0).unsigned_abs()))
+        .collect();
+
+    if changes.is_empty() {
+        return Ok(false);
+    }
+
+    guild_id.reorder_channels(&ctx.http, changes)
+        .await.bob_catch(ErrorKind::Admin, "Couldn't reorder the channels of the category.")?;
+
+    Ok(true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rooms_share_the_position_of_an_older_anchor() {
+        let rooms = [(5, ChannelId(30)), (0, ChannelId(40))];
+        assert_eq!(place_rooms(Some((1, ChannelId(10))), Some((2, ChannelId(20))), &rooms), Some(vec![1, 1]));
+    }
+
+    #[test]
+    fn rooms_older_than_the_anchor_go_below_it() {
+        let rooms = [(5, ChannelId(5)), (0, ChannelId(40))];
+        assert_eq!(place_rooms(Some((1, ChannelId(10))), Some((3, ChannelId(20))), &rooms), Some(vec![2, 2]));
+        assert_eq!(place_rooms(Some((1, ChannelId(10))), Some((2, ChannelId(20))), &rooms), None);
+    }
+
+    #[test]
+    fn rooms_out_of_id_order_get_increasing_positions() {
+        let rooms = [(0, ChannelId(40)), (0, ChannelId(30)), (0, ChannelId(50))];
+        assert_eq!(place_rooms(Some((3, ChannelId(10))), None, &rooms), Some(vec![3, 4, 4]));
+    }
+
+    #[test]
+    fn rooms_alone_start_from_the_topmost_position() {
+        let rooms = [(2, ChannelId(40)), (0, ChannelId(30))];
+        assert_eq!(place_rooms(None, None, &rooms), Some(vec![0, 1]));
+    }
+}
@@ -0,0 +1,140 @@
+//! This module contains a task to purge the data of the guilds the bot was removed from.
+
+use std::env;
+use std::collections::{HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, Duration};
+use once_cell::sync::{Lazy};
+use serenity::model::prelude::{GuildId};
+use serenity::http::Http;
+use serenity::utils::{shard_id};
+use tokio::time::{sleep};
+use crate::errors::{BobResult, BobCatch, ErrorKind};
+use crate::database::models::{WithGuildDeparture, WithGuildData, get_departures};
+
+
+/// The grace period used if `BOB_PURGE_GRACE_PERIOD` is not set: one week.
+const DEFAULT_GRACE_PERIOD: u64 = 604800;
+
+
+/// The purges which already have a [task_purge] waiting for them, along with the departure time they were scheduled for.
+static PENDING_PURGES: Lazy<Mutex<HashSet<(GuildId, SystemTime)>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+
+/// Get the time to wait before purging the data of a guild, reading it from the `BOB_PURGE_GRACE_PERIOD` environment
+/// variable (in seconds).
+pub fn purge_grace_period() -> BobResult<Duration> {
+    match env::var("BOB_PURGE_GRACE_PERIOD") {
+        Err(_) => Ok(Duration::from_secs(DEFAULT_GRACE_PERIOD)),
+        Ok(v) => Ok(Duration::from_secs(
+            v.parse::<u64>()
+                .bob_catch(ErrorKind::Host, "Invalid integer BOB_PURGE_GRACE_PERIOD")?
+        )),
+    }
+}
+
+
+/// _To be run in a `guild_delete` event._
+///
+/// Record that the bot was removed from the given [GuildId], and schedule a [task_purge] of its data.
+pub fn schedule_purge(http: Arc<Http>, guild_id: GuildId) -> BobResult<()> {
+    if guild_id.get_departure()?.is_none() {
+        guild_id.set_departure(SystemTime::now())?;
+    }
+
+    // Read the time back from the database, as it is stored with a lower precision
+    let departed_at = guild_id.get_departure()?
+        .bob_catch(ErrorKind::Developer, "Guild Departure was not stored in the database")?;
+
+    spawn_purge(http, guild_id, departed_at)
+}
+
+
+/// _To be run in a `guild_create` event._
+///
+/// Cancel the pending purge of the given [GuildId], if there is one.
+pub fn cancel_purge(guild_id: GuildId) -> BobResult<()> {
+    guild_id.unset_departure()?;
+    Ok(())
+}
+
+
+/// _To be run in a `ready` event._
+///
+/// Schedule again the purges that were pending when the bot was last stopped, for the guilds handled by the shard
+/// described by `shard` (as `[shard_id, shard_count]`).
+///
+/// Purges which are already waiting are not scheduled twice, as this event is received again after reconnecting.
+pub fn resume_purges(http: Arc<Http>, shard: Option<[u64; 2]>) -> BobResult<()> {
+    for (guild_id, departed_at) in get_departures()? {
+        if let Some([id, count]) = shard {
+            if shard_id(guild_id, count) != id {
+                continue;
+            }
+        }
+
+        spawn_purge(http.clone(), guild_id, departed_at)?;
+    }
+
+    Ok(())
+}
+
+
+/// Run [task_purge] in the background, logging its result, unless it is already waiting.
+fn spawn_purge(http: Arc<Http>, guild_id: GuildId, departed_at: SystemTime) -> BobResult<()> {
+    let key = (guild_id, departed_at);
+    {
+        let mut pending = PENDING_PURGES.lock().ok()
+            .bob_catch(ErrorKind::Developer, "Pending purges lock was poisoned")?;
+
+        if !pending.insert(key) {
+            return Ok(());
+        }
+    }
+
+    tokio::spawn(async move {
+        match task_purge(&http, guild_id, departed_at).await {
+            Ok(true) => info!("Purged data of <G:{}>", &guild_id),
+            Ok(false) => debug!("Not purging data of <G:{}>", &guild_id),
+            Err(e) => warn!("{}", e),
+        }
+
+        if let Ok(mut pending) = PENDING_PURGES.lock() {
+            pending.remove(&key);
+        }
+    });
+
+    Ok(())
+}
+
+
+/// Wait until the grace period following `departed_at` is over, then delete all the data stored about the given
+/// [GuildId], unless the bot was added back to it in the meantime.
+///
+/// # Returns
+///
+/// - `Ok(true)` if the data was purged.
+/// - `Ok(false)` if the purge was cancelled.
+/// - `Err(_)` if an error occurred.
+pub async fn task_purge(http: &Http, guild_id: GuildId, departed_at: SystemTime) -> BobResult<bool> {
+    debug!("Running task: purge | <G:{}>", &guild_id);
+
+    let purge_at = departed_at + purge_grace_period()?;
+    if let Ok(remaining) = purge_at.duration_since(SystemTime::now()) {
+        sleep(remaining).await;
+    }
+
+    // The bot may have been added back and removed again, in which case a newer task will take care of the purge
+    if guild_id.get_departure()? != Some(departed_at) {
+        return Ok(false);
+    }
+
+    // Events may have been missed while the bot was offline, so check with Discord before deleting anything
+    if guild_id.to_partial_guild(http).await.is_ok() {
+        cancel_purge(guild_id)?;
+        return Ok(false);
+    }
+
+    guild_id.purge_guild_data()?;
+    Ok(true)
+}
@@ -1,10 +1,16 @@
 use serenity::prelude::Context;
 use serenity::model::prelude::*;
 use serenity::model::application::interaction::application_command::{ApplicationCommandInteraction, CommandData};
+use serenity::model::application::command::{CommandOptionType};
 use crate::commands::build::command_build;
-use crate::commands::config::{command_config_cc, command_config_dt};
+use crate::commands::config::{command_config_cc, command_config_dt, command_config_preset, command_config_limits, command_config_builders, command_config_categories, command_config_overflow, command_config_order, command_config_cooldown, command_config_visibility, command_config_archive, command_config_companion, command_config_events, command_config_doctor, command_config_export, command_config_import, command_config_ban, command_config_unban, command_config_blocklist, command_config_modlog};
 use crate::commands::save::command_save;
+use crate::commands::invite::{command_invite, INVITE_COMMAND_NAME};
+use crate::commands::preset::{command_preset_info, command_preset_delete, command_preset_lock, command_preset_restrict, command_preset_unrestrict, command_preset_history, command_preset_rollback};
 use crate::errors::{BobCatch, ErrorKind, BobError, BobResult};
+use crate::utils::cooldowns::{check_cooldown};
+use crate::database::models::{WithCommandVisibilities};
+use std::time::Duration;
 
 
 pub async fn handle_command_interaction(ctx: &Context, interaction: &ApplicationCommandInteraction) -> BobResult<String> {
@@ -16,32 +22,119 @@ pub async fn handle_command_interaction(ctx: &Context, interaction: &Application
     let member = &interaction.member.as_ref()
         .bob_catch(ErrorKind::Developer, "Interaction has no member")?;
 
-    route_command_interaction(ctx, guild_id, channel_id, member, &interaction.data).await
+    route_command_interaction(ctx, guild_id, channel_id, member, interaction).await
 }
 
 
-pub async fn route_command_interaction(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
+/// Check whether the member who started the interaction has to wait before using the command again.
+pub fn check_command_cooldown(interaction: &ApplicationCommandInteraction) -> BobResult<Option<Duration>> {
+    let guild_id = interaction.guild_id
+        .bob_catch(ErrorKind::Developer, "Interaction has no GuildId")?;
+
+    let member = &interaction.member.as_ref()
+        .bob_catch(ErrorKind::Developer, "Interaction has no member")?;
+
+    check_cooldown(guild_id, member, &interaction.data.name)
+}
+
+
+/// The commands whose successful responses are only visible to whoever called them, unless configured otherwise.
+const EPHEMERAL_COMMANDS: [&str; 1] = ["config"];
+
+/// The commands whose responses are always only visible to whoever called them, as they contain private data.
+const PRIVATE_COMMANDS: [&str; 1] = ["config export"];
+
+
+/// Check whether the successful response to the interaction should only be visible to whoever started it.
+///
+/// Error responses are always only visible to whoever started the interaction.
+pub fn is_ephemeral_command(interaction: &ApplicationCommandInteraction) -> BobResult<bool> {
+    let guild_id = interaction.guild_id
+        .bob_catch(ErrorKind::Developer, "Interaction has no GuildId")?;
+
+    if PRIVATE_COMMANDS.contains(&command_path(&interaction.data).as_str()) {
+        return Ok(true);
+    }
+
+    let command = interaction.data.name.as_str();
+
+    match guild_id.get_command_visibility(command)? {
+        Some(ephemeral) => Ok(ephemeral),
+        None => Ok(EPHEMERAL_COMMANDS.contains(&command)),
+    }
+}
+
+
+/// Get the full name of the called command, including its subcommand if it has one.
+pub fn command_path(data: &CommandData) -> String {
+    match data.options.first() {
+        Some(option) if option.kind == CommandOptionType::SubCommand => format!("{} {}", &data.name, &option.name),
+        _ => data.name.clone(),
+    }
+}
+
+
+pub async fn route_command_interaction(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, member: &Member, interaction: &ApplicationCommandInteraction) -> BobResult<String> {
+    let data = &interaction.data;
+
     match data.name.as_str() {
         "build"  => command_build(ctx, guild_id, channel_id, member, data).await,
         "save"   => command_save(ctx, guild_id, channel_id, member, data).await,
-        "config" => route_config(ctx, guild_id, channel_id, member, data).await,
+        "config" => route_config(ctx, guild_id, channel_id, member, interaction).await,
+        "preset" => route_preset(ctx, guild_id, channel_id, member, data).await,
+        INVITE_COMMAND_NAME => command_invite(ctx, guild_id, channel_id, member, data).await,
         _        => command_invalid().await,
     }
 }
 
 
-pub async fn route_config(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
-    let option = data.options.get(0)
+pub async fn route_config(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, member: &Member, interaction: &ApplicationCommandInteraction) -> BobResult<String> {
+    let option = interaction.data.options.first()
         .bob_catch(ErrorKind::Developer, "First interaction option isn't SubCommand")?;
 
     match option.name.as_str() {
         "cc" => command_config_cc(ctx, guild_id, channel_id, member, &option.options).await,
         "dt" => command_config_dt(ctx, guild_id, channel_id, member, &option.options).await,
+        "preset" => command_config_preset(ctx, guild_id, channel_id, member, &option.options).await,
+        "limits" => command_config_limits(ctx, guild_id, channel_id, member, &option.options).await,
+        "builders" => command_config_builders(ctx, guild_id, channel_id, member, &option.options).await,
+        "categories" => command_config_categories(ctx, guild_id, channel_id, member, &option.options).await,
+        "overflow" => command_config_overflow(ctx, guild_id, channel_id, member, &option.options).await,
+        "order" => command_config_order(ctx, guild_id, channel_id, member, &option.options).await,
+        "cooldown" => command_config_cooldown(ctx, guild_id, channel_id, member, &option.options).await,
+        "visibility" => command_config_visibility(ctx, guild_id, channel_id, member, &option.options).await,
+        "archive" => command_config_archive(ctx, guild_id, channel_id, member, &option.options).await,
+        "companion" => command_config_companion(ctx, guild_id, channel_id, member, &option.options).await,
+        "events" => command_config_events(ctx, guild_id, channel_id, member, &option.options).await,
+        "doctor" => command_config_doctor(ctx, guild_id, channel_id, member, &option.options).await,
+        "export" => command_config_export(ctx, guild_id, interaction, member, &option.options).await,
+        "import" => command_config_import(ctx, guild_id, channel_id, member, &option.options).await,
+        "ban" => command_config_ban(ctx, guild_id, channel_id, member, &option.options).await,
+        "unban" => command_config_unban(ctx, guild_id, channel_id, member, &option.options).await,
+        "blocklist" => command_config_blocklist(ctx, guild_id, channel_id, member, &option.options).await,
+        "modlog" => command_config_modlog(ctx, guild_id, channel_id, member, &option.options).await,
         _    => command_invalid().await
     }
 }
 
 
+pub async fn route_preset(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, member: &Member, data: &CommandData) -> BobResult<String> {
+    let option = data.options.first()
+        .bob_catch(ErrorKind::Developer, "First interaction option isn't SubCommand")?;
+
+    match option.name.as_str() {
+        "info"       => command_preset_info(ctx, guild_id, channel_id, member, &option.options).await,
+        "delete"     => command_preset_delete(ctx, guild_id, channel_id, member, &option.options).await,
+        "lock"       => command_preset_lock(ctx, guild_id, channel_id, member, &option.options).await,
+        "restrict"   => command_preset_restrict(ctx, guild_id, channel_id, member, &option.options).await,
+        "unrestrict" => command_preset_unrestrict(ctx, guild_id, channel_id, member, &option.options).await,
+        "history"    => command_preset_history(ctx, guild_id, channel_id, member, &option.options).await,
+        "rollback"   => command_preset_rollback(ctx, guild_id, channel_id, member, &option.options).await,
+        _            => command_invalid().await
+    }
+}
+
+
 async fn command_invalid() -> BobResult<String> {
     Err(
         BobError::from_msg(ErrorKind::Developer, "Invalid command name")
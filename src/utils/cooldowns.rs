@@ -0,0 +1,267 @@
+//! This module contains the token buckets used to limit how often commands can be used.
+
+use std::env;
+use std::collections::{HashMap};
+use std::sync::{Mutex};
+use std::time::{Duration, Instant};
+use once_cell::sync::{Lazy};
+use serenity::model::prelude::{GuildId, UserId, Member};
+use crate::errors::{BobResult, BobCatch, BobError, ErrorKind};
+use crate::database::models::{WithCooldownExemptions};
+
+
+/// The commands which are subject to cooldowns.
+pub const LIMITED_COMMANDS: [&str; 2] = ["build", "save"];
+
+/// The rate used if `BOB_COOLDOWN_USER` is not set: 3 commands per minute.
+const DEFAULT_USER_RATE: &str = "3/60";
+
+/// The rate used if `BOB_COOLDOWN_GUILD` is not set: 20 commands per minute.
+const DEFAULT_GUILD_RATE: &str = "20/60";
+
+/// Buckets are forgotten once there are more than this number of them, if they're full.
+const MAX_IDLE_BUCKETS: usize = 1000;
+
+
+/// How many commands can be used in a given period of time.
+#[derive(Debug, Clone, Copy)]
+pub struct Rate {
+    /// The number of commands which can be used in a burst.
+    pub capacity: u32,
+    /// The time it takes for a bucket to refill completely.
+    pub period: Duration,
+}
+
+impl Rate {
+    /// Parse a [Rate] in the `{capacity}/{seconds}` format, or `off` to disable the cooldown.
+    pub fn parse(value: &str) -> BobResult<Option<Rate>> {
+        if value == "off" {
+            return Ok(None);
+        }
+
+        let (capacity, seconds) = value.split_once('/')
+            .bob_catch(ErrorKind::Host, "Rates should be in the {capacity}/{seconds} format")?;
+
+        let capacity = capacity.trim().parse::<u32>()
+            .bob_catch(ErrorKind::Host, "Invalid rate capacity")?;
+        let seconds = seconds.trim().parse::<u64>()
+            .bob_catch(ErrorKind::Host, "Invalid rate period")?;
+
+        if capacity == 0 || seconds == 0 {
+            return Err(BobError::from_msg(ErrorKind::Host, "Rates must have a positive capacity and period"));
+        }
+
+        Ok(Some(Rate { capacity, period: Duration::from_secs(seconds) }))
+    }
+
+    /// Read a [Rate] from the given environment variable, falling back to `default` if it isn't set.
+    fn from_env(name: &str, default: &str) -> BobResult<Option<Rate>> {
+        match env::var(name) {
+            Ok(value) => Rate::parse(&value),
+            Err(_) => Rate::parse(default),
+        }
+    }
+
+    /// The number of tokens regained every second.
+    fn tokens_per_second(&self) -> f64 {
+        f64::from(self.capacity) / self.period.as_secs_f64()
+    }
+}
+
+
+/// A bucket which loses a token every time a command is used, and slowly refills over time.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    rate: Rate,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full [TokenBucket].
+    fn full(rate: Rate, now: Instant) -> Self {
+        TokenBucket { rate, tokens: f64::from(rate.capacity), updated: now }
+    }
+
+    /// Refill the bucket according to the time elapsed since its last update.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate.tokens_per_second()).min(f64::from(self.rate.capacity));
+        self.updated = now;
+    }
+
+    /// Get how long it will take for the bucket to have a token available.
+    fn wait_time(&self) -> Duration {
+        match self.tokens >= 1.0 {
+            true => Duration::ZERO,
+            false => Duration::from_secs_f64((1.0 - self.tokens) / self.rate.tokens_per_second()),
+        }
+    }
+
+    /// Check whether the bucket is full, and can be forgotten.
+    fn is_full(&self) -> bool {
+        self.tokens >= f64::from(self.rate.capacity)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BucketKey {
+    User(GuildId, UserId),
+    Guild(GuildId),
+}
+
+
+static BUCKETS: Lazy<Mutex<HashMap<BucketKey, TokenBucket>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+
+/// Take a token from each of the given buckets, but only if all of them have one available.
+///
+/// # Returns
+///
+/// - `Ok(None)` if the tokens were taken.
+/// - `Ok(Some(wait))` if one of the buckets is empty, and how long to wait before trying again.
+fn take_tokens(keys: &[(BucketKey, Rate)], now: Instant) -> BobResult<Option<Duration>> {
+    let mut buckets = BUCKETS.lock().ok()
+        .bob_catch(ErrorKind::Developer, "Cooldown buckets lock was poisoned")?;
+
+    let mut wait = Duration::ZERO;
+    for (key, rate) in keys {
+        let bucket = buckets.entry(*key).or_insert_with(|| TokenBucket::full(*rate, now));
+        // The rate may have been changed since the bucket was created
+        bucket.rate = *rate;
+        bucket.refill(now);
+        wait = wait.max(bucket.wait_time());
+    }
+
+    if wait > Duration::ZERO {
+        return Ok(Some(wait));
+    }
+
+    for (key, _) in keys {
+        if let Some(bucket) = buckets.get_mut(key) {
+            bucket.tokens -= 1.0;
+        }
+    }
+
+    if buckets.len() > MAX_IDLE_BUCKETS {
+        buckets.retain(|_, bucket| {
+            bucket.refill(now);
+            !bucket.is_full()
+        });
+    }
+
+    Ok(None)
+}
+
+
+/// _To be run before handling a command._
+///
+/// Check whether the given [Member] can use the command named `command` right now, consuming a token from both their
+/// bucket and the bucket of the guild.
+///
+/// # Returns
+///
+/// - `Ok(None)` if the command can be used.
+/// - `Ok(Some(wait))` if the command can't be used yet, and how long to wait before using it again.
+/// - `Err(_)` if an error occurred.
+pub fn check_cooldown(guild_id: GuildId, member: &Member, command: &str) -> BobResult<Option<Duration>> {
+    if !LIMITED_COMMANDS.contains(&command) {
+        return Ok(None);
+    }
+
+    let exemptions = guild_id.get_cooldown_exemptions()?;
+    if member.roles.iter().any(|r| exemptions.contains(r)) {
+        return Ok(None);
+    }
+
+    let mut keys = vec![];
+    if let Some(rate) = Rate::from_env("BOB_COOLDOWN_USER", DEFAULT_USER_RATE)? {
+        keys.push((BucketKey::User(guild_id, member.user.id), rate));
+    }
+    if let Some(rate) = Rate::from_env("BOB_COOLDOWN_GUILD", DEFAULT_GUILD_RATE)? {
+        keys.push((BucketKey::Guild(guild_id), rate));
+    }
+
+    take_tokens(&keys, Instant::now())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(capacity: u32, seconds: u64) -> Rate {
+        Rate { capacity, period: Duration::from_secs(seconds) }
+    }
+
+    #[test]
+    fn parse_accepts_rates_and_off() {
+        let parsed = Rate::parse(" 3 / 60 ").unwrap().unwrap();
+        assert_eq!(parsed.capacity, 3);
+        assert_eq!(parsed.period, Duration::from_secs(60));
+
+        assert!(Rate::parse("off").unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_rates() {
+        assert!(Rate::parse("3").is_err());
+        assert!(Rate::parse("a/60").is_err());
+        assert!(Rate::parse("3/-1").is_err());
+        assert!(Rate::parse("0/60").is_err());
+        assert!(Rate::parse("3/0").is_err());
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(rate(2, 10), now);
+        bucket.tokens = 0.0;
+
+        bucket.refill(now + Duration::from_secs(5));
+        assert!((bucket.tokens - 1.0).abs() < 1e-9);
+
+        // Refilling never goes above the capacity
+        bucket.refill(now + Duration::from_secs(60));
+        assert!(bucket.is_full());
+        assert!((bucket.tokens - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bucket_wait_time() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(rate(1, 10), now);
+        assert_eq!(bucket.wait_time(), Duration::ZERO);
+
+        bucket.tokens = 0.5;
+        assert_eq!(bucket.wait_time(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn take_tokens_until_empty() {
+        let now = Instant::now();
+        let keys = [(BucketKey::Guild(GuildId(1001)), rate(2, 60))];
+
+        assert_eq!(take_tokens(&keys, now).unwrap(), None);
+        assert_eq!(take_tokens(&keys, now).unwrap(), None);
+        assert_eq!(take_tokens(&keys, now).unwrap(), Some(Duration::from_secs(30)));
+
+        // A token is regained after half the period
+        assert_eq!(take_tokens(&keys, now + Duration::from_secs(30)).unwrap(), None);
+    }
+
+    #[test]
+    fn take_tokens_from_all_buckets_or_none() {
+        let now = Instant::now();
+        let user = (BucketKey::User(GuildId(1002), UserId(1)), rate(5, 60));
+        let guild = (BucketKey::Guild(GuildId(1002)), rate(1, 60));
+
+        assert_eq!(take_tokens(&[user, guild], now).unwrap(), None);
+        assert!(take_tokens(&[user, guild], now).unwrap().is_some());
+
+        // The user bucket didn't lose a token when the guild bucket was empty
+        let buckets = BUCKETS.lock().unwrap();
+        assert!((buckets[&user.0].tokens - 4.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,230 @@
+//! This module contains the checks run by `/config doctor` to find misconfigurations before they cause errors.
+
+use std::collections::{HashMap};
+use serenity::model::prelude::*;
+use serenity::prelude::{Context};
+use crate::errors::*;
+use crate::extensions::*;
+use crate::database::models::{WithCommandChannel, WithDeletionTime, WithBuildCategories, CanGetPresetData, PresetTarget, WithGuildSettings};
+use crate::utils::permission_overwrites::{effective_permissions};
+
+
+/// The permissions Bob needs in every category where channels are built.
+fn build_permissions() -> Permissions {
+    Permissions::VIEW_CHANNEL | Permissions::MANAGE_CHANNELS | Permissions::MANAGE_ROLES | Permissions::CONNECT | Permissions::MOVE_MEMBERS
+}
+
+/// The permissions Bob needs in the command channel and in the moderation channel.
+fn command_channel_permissions() -> Permissions {
+    Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES
+}
+
+/// The permissions Bob needs in the archive channel.
+fn archive_channel_permissions() -> Permissions {
+    Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES
+}
+
+
+/// The outcome of a single check.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// Whether the check passed.
+    pub ok: bool,
+    /// What was checked.
+    pub description: String,
+    /// How to fix the problem, if the check failed.
+    pub fix: Option<String>,
+}
+
+impl Diagnostic {
+    fn pass(description: String) -> Self {
+        Diagnostic { ok: true, description, fix: None }
+    }
+
+    fn fail(description: String, fix: String) -> Self {
+        Diagnostic { ok: false, description, fix: Some(fix) }
+    }
+}
+
+
+/// Describe the given [Permissions], or `nothing` if they are empty.
+fn describe_permissions(permissions: Permissions) -> String {
+    match permissions.is_empty() {
+        true => "nothing".to_string(),
+        false => permissions.get_permission_names().join(", "),
+    }
+}
+
+
+/// Check that Bob has all the `required` [Permissions] in the given [GuildChannel].
+fn check_permissions(guild: &PartialGuild, bob: &Member, channel: &GuildChannel, required: Permissions) -> BobResult<Diagnostic> {
+    let permissions = guild.user_permissions_in(channel, bob)
+        .bob_catch(ErrorKind::Developer, "Couldn't compute my permissions.")?;
+
+    let missing = required - permissions;
+    match missing.is_empty() {
+        true => Ok(Diagnostic::pass(format!("I have the permissions I need in {}.", channel.mention()))),
+        false => Ok(Diagnostic::fail(
+            format!("I'm missing some permissions in {}: {}.", channel.mention(), describe_permissions(missing)),
+            format!("Allow {} for my role in {}.", describe_permissions(missing), channel.mention()),
+        )),
+    }
+}
+
+
+/// Check that the `description`d channel with the given [ChannelId] still exists, and that Bob has all the `required`
+/// [Permissions] in it; if it doesn't exist anymore, suggest setting a new one with the given `command`.
+fn check_channel(guild: &PartialGuild, bob: &Member, channels: &HashMap<ChannelId, GuildChannel>, channel_id: ChannelId, description: &str, command: &str, required: Permissions) -> BobResult<Diagnostic> {
+    match channels.get(&channel_id) {
+        None => Ok(Diagnostic::fail(
+            format!("The {} {} doesn't exist anymore.", description, channel_id.mention()),
+            format!("Set a new one with `{}`.", command),
+        )),
+        Some(channel) => check_permissions(guild, bob, channel, required),
+    }
+}
+
+
+/// Check the command channel and the deletion time of the guild.
+fn check_settings(guild: &PartialGuild, bob: &Member, channels: &HashMap<ChannelId, GuildChannel>) -> BobResult<Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+
+    match guild.id.get_command_channel()? {
+        None => diagnostics.push(Diagnostic::fail(
+            "No command channel has been set.".to_string(),
+            "Set one with `/config cc`.".to_string(),
+        )),
+        Some(cc) => diagnostics.push(check_channel(guild, bob, channels, cc, "command channel", "/config cc", command_channel_permissions())?),
+    }
+
+    match guild.id.get_deletion_time()? {
+        None => diagnostics.push(Diagnostic::fail(
+            "No deletion time has been set.".to_string(),
+            "Set one with `/config dt`.".to_string(),
+        )),
+        Some(dt) => diagnostics.push(Diagnostic::pass(format!("Empty channels are deleted after {} seconds.", dt.as_secs()))),
+    }
+
+    Ok(diagnostics)
+}
+
+
+/// Check Bob's permissions in every category where channels can be built.
+fn check_categories(guild: &PartialGuild, bob: &Member, channels: &HashMap<ChannelId, GuildChannel>) -> BobResult<Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+
+    let allowed = guild.id.get_build_categories()?;
+    let categories: Vec<ChannelId> = match allowed.is_empty() {
+        true => channels.values().filter(|c| c.kind == ChannelType::Category).map(|c| c.id).collect(),
+        false => allowed,
+    };
+
+    for category_id in categories {
+        match channels.get(&category_id) {
+            None => diagnostics.push(Diagnostic::fail(
+                format!("The build category {} doesn't exist anymore.", category_id.mention()),
+                format!("Remove it with `/config categories category:{} allowed:False`.", category_id),
+            )),
+            Some(category) => diagnostics.push(check_permissions(guild, bob, category, build_permissions())?),
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+
+/// Check the overflow categories, the rooms of scheduled events, the archive channel and the moderation channel of the
+/// guild, if they have been set.
+fn check_targets(guild: &PartialGuild, bob: &Member, channels: &HashMap<ChannelId, GuildChannel>) -> BobResult<Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+    let settings = guild.id.get_guild_settings()?;
+
+    for (category, overflow) in settings.overflow_categories {
+        let command = format!("/config overflow category:{}", category);
+        diagnostics.push(check_channel(guild, bob, channels, overflow, "overflow category", &command, build_permissions())?);
+    }
+
+    if let Some((preset, _, category)) = settings.event_rooms {
+        if effective_permissions(guild, bob, &[]).manage_events() {
+            diagnostics.push(Diagnostic::pass("I can move scheduled events to their rooms.".to_string()));
+        }
+        else {
+            diagnostics.push(Diagnostic::fail(
+                "I can't move scheduled events to their rooms, as I'm missing Manage Events.".to_string(),
+                "Allow Manage Events for my role in the server settings.".to_string(),
+            ));
+        }
+
+        if guild.id.get_preset(&preset)?.is_none() {
+            diagnostics.push(Diagnostic::fail(
+                format!("The preset **{}** of event rooms doesn't exist anymore.", &preset),
+                "Choose another one with `/config events`.".to_string(),
+            ));
+        }
+
+        if let Some(category) = category {
+            diagnostics.push(check_channel(guild, bob, channels, category, "category of event rooms", "/config events", build_permissions())?);
+        }
+    }
+
+    if let Some(archive) = settings.archive_channel {
+        diagnostics.push(check_channel(guild, bob, channels, archive, "archive channel", "/config archive", archive_channel_permissions())?);
+    }
+
+    if let Some(modlog) = settings.moderation_channel {
+        diagnostics.push(check_channel(guild, bob, channels, modlog, "moderation channel", "/config modlog", command_channel_permissions())?);
+    }
+
+    Ok(diagnostics)
+}
+
+
+/// Check that every role referenced by the presets of the guild still exists.
+fn check_presets(guild: &PartialGuild) -> BobResult<Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+
+    for (name, data) in guild.id.get_all_preset_data()? {
+        let missing: Vec<String> = data.permissions.iter()
+            .filter_map(|p| match &p.target {
+                PresetTarget::Role { name } if guild.role_by_name(name).is_none() => Some(format!("@{}", name)),
+                PresetTarget::RoleId { id } if !guild.roles.contains_key(&RoleId(*id)) => Some(format!("<deleted role {}>", id)),
+                _ => None,
+            })
+            .collect();
+
+        match missing.is_empty() {
+            true => diagnostics.push(Diagnostic::pass(format!("All the roles of the preset **{}** exist.", &name))),
+            false => diagnostics.push(Diagnostic::fail(
+                format!("The preset **{}** references missing roles: {}.", &name, missing.join(", ")),
+                format!("Recreate the roles, or save **{}** again with `/save`.", &name),
+            )),
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+
+/// Run all the checks on the given [PartialGuild].
+pub async fn diagnose(ctx: &Context, guild: &PartialGuild) -> BobResult<Vec<Diagnostic>> {
+    let bob = guild.ext_member(&ctx.http, ctx.cache.current_user_id()).await?;
+
+    let channels = guild.id.channels(&ctx.http)
+        .await.bob_catch(ErrorKind::External, "Couldn't retrieve the channels of the server.")?;
+
+    let mut diagnostics = vec![];
+
+    match effective_permissions(guild, &bob, &[]).administrator() {
+        true => diagnostics.push(Diagnostic::pass("I'm an Administrator.".to_string())),
+        false => diagnostics.push(Diagnostic::pass(
+            "I'm not an Administrator, so channel owners will only get the permissions I have myself.".to_string(),
+        )),
+    }
+
+    diagnostics.extend(check_settings(guild, &bob, &channels)?);
+    diagnostics.extend(check_categories(guild, &bob, &channels)?);
+    diagnostics.extend(check_targets(guild, &bob, &channels)?);
+    diagnostics.extend(check_presets(guild)?);
+
+    Ok(diagnostics)
+}
@@ -0,0 +1,805 @@
+//! This module contains the file format used to export the configuration of a guild and to import it into another.
+
+use std::collections::{HashMap};
+use std::convert::{TryFrom};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serenity::model::prelude::*;
+use serenity::http::Http;
+use serde::{Serialize, Deserialize};
+use crate::errors::*;
+use crate::database::models::{PresetData, PresetTarget, CanGetPresetData, GuildSettings, WithGuildSettings, CompanionMode, RoomOrdering, ImportedPreset};
+use crate::utils::channel_names::{Channelizable};
+use crate::utils::moderation::{compile_pattern};
+
+
+/// The file formats a [GuildExport] can be stored in.
+#[derive(Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Json,
+    Toml,
+}
+
+impl ExportFormat {
+    /// Guess the [ExportFormat] of a file from its name.
+    pub fn from_filename(filename: &str) -> BobResult<Self> {
+        let filename = filename.to_ascii_lowercase();
+
+        if filename.ends_with(".json") {
+            Ok(ExportFormat::Json)
+        }
+        else if filename.ends_with(".toml") {
+            Ok(ExportFormat::Toml)
+        }
+        else {
+            Err(BobError::from_msg(ErrorKind::User, "Only `.json` and `.toml` files can be imported."))
+        }
+    }
+
+    /// The file extension of the format, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Toml => "toml",
+        }
+    }
+}
+
+
+/// The id and the name of a Discord object, used to find the matching object in another guild.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedName {
+    pub id: u64,
+    pub name: String,
+}
+
+/// A preset, as it is stored in a [GuildExport].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedPreset {
+    pub name: String,
+    pub data: PresetData,
+    /// The member who created the preset, if it is known.
+    #[serde(default)]
+    pub author: Option<u64>,
+    #[serde(default)]
+    pub locked: bool,
+    /// The roles allowed to use the preset; if empty, everyone is allowed to use it.
+    #[serde(default)]
+    pub allowed_roles: Vec<u64>,
+}
+
+/// The default preset of a category, or of the whole guild if `category` is [None].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedDefaultPreset {
+    pub category: Option<ExportedName>,
+    pub preset: String,
+}
+
+/// How the rooms of scheduled events are built.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedEventRooms {
+    pub preset: String,
+    /// How long before the start of an event its room is built, in seconds.
+    pub lead_time: u64,
+    pub category: Option<ExportedName>,
+}
+
+/// The limits on the number of channels Bob can create.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExportedRoomLimits {
+    pub per_member: Option<u32>,
+    pub per_category: Option<u32>,
+    pub per_guild: Option<u32>,
+}
+
+/// A category which overflows into another one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedOverflow {
+    pub category: ExportedName,
+    pub overflow: ExportedName,
+}
+
+/// How the channels built in a category are ordered.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedOrdering {
+    pub category: ExportedName,
+    pub ordering: String,
+    pub anchor: Option<ExportedName>,
+}
+
+/// Whether the responses of a command are only visible to whoever called it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedVisibility {
+    pub command: String,
+    pub ephemeral: bool,
+}
+
+/// A member banned from building channels.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedBan {
+    /// The id of the member, named in [GuildExport::members].
+    pub member: u64,
+    /// The unix timestamp after which the ban expires, or [None] if it's permanent.
+    pub expires_at: Option<i64>,
+    pub reason: Option<String>,
+}
+
+/// The settings of a guild added to the export format in version 2.
+///
+/// Roles and members are referred to by id, and named in [GuildExport::roles] and [GuildExport::members].
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExportedSettings {
+    pub default_presets: Vec<ExportedDefaultPreset>,
+    pub event_rooms: Option<ExportedEventRooms>,
+    pub companion_mode: Option<String>,
+    pub archive_channel: Option<ExportedName>,
+    pub room_limits: ExportedRoomLimits,
+    pub cooldown_exemptions: Vec<u64>,
+    pub overflow_categories: Vec<ExportedOverflow>,
+    pub build_categories: Vec<ExportedName>,
+    pub category_orderings: Vec<ExportedOrdering>,
+    pub command_visibilities: Vec<ExportedVisibility>,
+    pub builder_roles: Vec<u64>,
+    pub build_bans: Vec<ExportedBan>,
+    pub blocked_names: Vec<String>,
+    pub moderation_channel: Option<ExportedName>,
+}
+
+/// The configuration of a guild and all of its presets.
+///
+/// Version 1 only contains the command channel, the deletion time and the presets; version 2 adds all the other
+/// settings in [GuildExport::settings]. When importing a version 1 export, the other settings are left unchanged.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GuildExport {
+    /// The version of the export format, to detect files created by incompatible versions of the bot.
+    pub version: u32,
+    /// The guild the export was created from.
+    pub guild_id: u64,
+    pub command_channel: Option<ExportedName>,
+    /// The deletion time, in seconds.
+    pub deletion_time: Option<u64>,
+    /// The names of the roles referenced by the presets and the settings.
+    pub roles: Vec<ExportedName>,
+    /// The names of the members referenced by the presets and the settings.
+    pub members: Vec<ExportedName>,
+    pub presets: Vec<ExportedPreset>,
+    /// _Since version 2._
+    #[serde(default)]
+    pub settings: Option<ExportedSettings>,
+}
+
+impl GuildExport {
+    /// The current version of the export format.
+    pub const VERSION: u32 = 2;
+
+    /// Create a [GuildExport] from the configuration of the given [PartialGuild].
+    pub async fn collect(http: &Http, guild: &PartialGuild) -> BobResult<Self> {
+        let settings = guild.id.get_guild_settings()?;
+
+        let channels = guild.channels(http)
+            .await.bob_catch(ErrorKind::External, "Couldn't retrieve the channels of the server.")?;
+        let channel = |cid: ChannelId| ExportedName {
+            id: cid.0,
+            name: channels.get(&cid).map(|c| c.name.clone()).unwrap_or_default(),
+        };
+
+        let presets: Vec<ExportedPreset> = guild.id.get_all_presets()?
+            .into_iter()
+            .map(|preset| Ok(ExportedPreset {
+                data: preset.data()?,
+                author: preset.author()?.map(|uid| uid.0),
+                locked: preset.locked,
+                allowed_roles: preset.allowed_roles()?.iter().map(|r| r.0).collect(),
+                name: preset.preset_name,
+            }))
+            .collect::<BobResult<_>>()?;
+
+        let mut role_ids: Vec<RoleId> = settings.cooldown_exemptions.iter().chain(settings.builder_roles.iter()).copied().collect();
+        let mut member_ids: Vec<UserId> = settings.build_bans.iter().map(|(uid, ..)| *uid).collect();
+        for preset in &presets {
+            member_ids.extend(preset.author.map(UserId));
+            role_ids.extend(preset.allowed_roles.iter().copied().map(RoleId));
+            for permission in &preset.data.permissions {
+                match permission.target {
+                    PresetTarget::RoleId { id } => role_ids.push(RoleId(id)),
+                    PresetTarget::Member { id } => member_ids.push(UserId(id)),
+                    _ => {},
+                }
+            }
+        }
+
+        let mut roles: HashMap<u64, String> = HashMap::new();
+        for rid in role_ids {
+            if let Some(role) = guild.roles.get(&rid) {
+                roles.insert(rid.0, role.name.clone());
+            }
+        }
+
+        let mut members: HashMap<u64, String> = HashMap::new();
+        for uid in member_ids {
+            if members.contains_key(&uid.0) {
+                continue;
+            }
+            if let Ok(member) = guild.member(http, uid).await {
+                members.insert(uid.0, member.user.name.clone());
+            }
+        }
+
+        let exported = ExportedSettings {
+            default_presets: settings.default_presets.iter()
+                .map(|(category, preset)| ExportedDefaultPreset { category: category.map(channel), preset: preset.clone() })
+                .collect(),
+            event_rooms: settings.event_rooms.as_ref()
+                .map(|(preset, lead_time, category)| ExportedEventRooms {
+                    preset: preset.clone(),
+                    lead_time: lead_time.as_secs(),
+                    category: category.map(channel),
+                }),
+            companion_mode: settings.companion_mode.map(|cm| cm.as_str().to_string()),
+            archive_channel: settings.archive_channel.map(channel),
+            room_limits: ExportedRoomLimits {
+                per_member: settings.room_limits.0,
+                per_category: settings.room_limits.1,
+                per_guild: settings.room_limits.2,
+            },
+            cooldown_exemptions: settings.cooldown_exemptions.iter().map(|r| r.0).collect(),
+            overflow_categories: settings.overflow_categories.iter()
+                .map(|(category, overflow)| ExportedOverflow { category: channel(*category), overflow: channel(*overflow) })
+                .collect(),
+            build_categories: settings.build_categories.iter().map(|c| channel(*c)).collect(),
+            category_orderings: settings.category_orderings.iter()
+                .map(|(category, ordering, anchor)| ExportedOrdering {
+                    category: channel(*category),
+                    ordering: ordering.as_str().to_string(),
+                    anchor: anchor.map(channel),
+                })
+                .collect(),
+            command_visibilities: settings.command_visibilities.iter()
+                .map(|(command, ephemeral)| ExportedVisibility { command: command.clone(), ephemeral: *ephemeral })
+                .collect(),
+            builder_roles: settings.builder_roles.iter().map(|r| r.0).collect(),
+            build_bans: settings.build_bans.iter()
+                .map(|(uid, expires_at, reason)| Ok(ExportedBan {
+                    member: uid.0,
+                    expires_at: expires_at.map(unix_timestamp).transpose()?,
+                    reason: reason.clone(),
+                }))
+                .collect::<BobResult<_>>()?,
+            blocked_names: settings.blocked_names.clone(),
+            moderation_channel: settings.moderation_channel.map(channel),
+        };
+
+        Ok(GuildExport {
+            version: GuildExport::VERSION,
+            guild_id: guild.id.0,
+            command_channel: settings.command_channel.map(channel),
+            deletion_time: settings.deletion_time.map(|d| d.as_secs()),
+            roles: roles.into_iter().map(|(id, name)| ExportedName { id, name }).collect(),
+            members: members.into_iter().map(|(id, name)| ExportedName { id, name }).collect(),
+            presets,
+            settings: Some(exported),
+        })
+    }
+
+    /// Serialize the [GuildExport] in the given [ExportFormat].
+    pub fn serialize(&self, format: ExportFormat) -> BobResult<String> {
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&self)
+                    .bob_catch(ErrorKind::Developer, "Couldn't serialize GuildExport to JSON.")
+            },
+            ExportFormat::Toml => {
+                // Going through a Value sorts the keys so that tables are placed after plain values
+                let value = toml::Value::try_from(self)
+                    .bob_catch(ErrorKind::Developer, "Couldn't serialize GuildExport to TOML.")?;
+
+                toml::to_string_pretty(&value)
+                    .bob_catch(ErrorKind::Developer, "Couldn't serialize GuildExport to TOML.")
+            },
+        }
+    }
+
+    /// Deserialize a [GuildExport] stored in the given [ExportFormat].
+    pub fn deserialize(data: &[u8], format: ExportFormat) -> BobResult<Self> {
+        let export = match format {
+            ExportFormat::Json => {
+                serde_json::from_slice::<GuildExport>(data)
+                    .bob_catch(ErrorKind::User, "The file isn't a valid JSON export.")?
+            },
+            ExportFormat::Toml => {
+                toml::from_slice::<GuildExport>(data)
+                    .bob_catch(ErrorKind::User, "The file isn't a valid TOML export.")?
+            },
+        };
+
+        if export.version == 0 || export.version > GuildExport::VERSION {
+            return Err(BobError::from_msg(ErrorKind::User, "The file was exported by an incompatible version of the bot."))
+        }
+
+        Ok(export)
+    }
+
+    /// Convert the [GuildExport] into a [GuildImport] for the given [PartialGuild], remapping the roles, members and
+    /// channels that don't exist in it by name.
+    pub async fn remap(self, http: &Http, guild: &PartialGuild) -> BobResult<GuildImport> {
+        let mut remapper = Remapper {
+            http,
+            guild,
+            source_gid: self.guild_id,
+            channels: guild.channels(http)
+                .await.bob_catch(ErrorKind::External, "Couldn't retrieve the channels of the server.")?,
+            role_names: self.roles.into_iter().map(|r| (r.id, r.name)).collect(),
+            member_names: self.members.into_iter().map(|m| (m.id, m.name)).collect(),
+            role_cache: HashMap::new(),
+            missing_roles: vec![],
+            member_cache: HashMap::new(),
+            notes: vec![],
+        };
+
+        let mut settings = match &self.settings {
+            Some(_) => GuildSettings::default(),
+            None => {
+                remapper.notes.push("📄 The file was exported by an older version of the bot: only the command channel, the deletion time and the presets will be imported.".to_string());
+                guild.id.get_guild_settings()?
+            },
+        };
+
+        settings.command_channel = match &self.command_channel {
+            Some(cc) => remapper.channel(cc, Some(ChannelType::Text), "Command channel"),
+            None => None,
+        };
+        settings.deletion_time = self.deletion_time.map(Duration::from_secs);
+
+        if let Some(exported) = self.settings {
+            remapper.settings(exported, &mut settings).await?;
+        }
+
+        let mut presets = vec![];
+        for preset in self.presets {
+            let mut data = preset.data;
+            let mut permissions = vec![];
+
+            for mut permission in data.permissions {
+                match &permission.target {
+                    PresetTarget::Everyone | PresetTarget::Creator => {},
+                    PresetTarget::Role { name } => {
+                        if guild.role_by_name(name).is_none() && !remapper.missing_roles.contains(name) {
+                            remapper.notes.push(format!("❔ Role `@{}` doesn't exist in this server, and its overwrites will be ignored until it is created.", &name));
+                            remapper.missing_roles.push(name.clone());
+                        }
+                    },
+                    PresetTarget::RoleId { id } => {
+                        match remapper.role(RoleId(*id)) {
+                            Some(target) => permission.target = target,
+                            None => continue,
+                        }
+                    },
+                    PresetTarget::Member { id } => {
+                        match remapper.member(UserId(*id)).await {
+                            Some(uid) => permission.target = PresetTarget::Member { id: uid.0 },
+                            None => continue,
+                        }
+                    },
+                }
+                permissions.push(permission);
+            }
+
+            data.permissions = permissions;
+
+            let author = match preset.author {
+                Some(uid) => remapper.member(UserId(uid)).await,
+                None => None,
+            };
+
+            let mut allowed_roles = vec![];
+            for rid in preset.allowed_roles.iter() {
+                allowed_roles.extend(remapper.role_id(RoleId(*rid)));
+            }
+            if allowed_roles.is_empty() && !preset.allowed_roles.is_empty() {
+                remapper.notes.push(format!("⚠️ None of the roles allowed to use preset `{}` exist in this server, so everyone will be able to use it.", &preset.name));
+            }
+
+            presets.push(ImportedPreset {
+                name: preset.name.channelify(),
+                data,
+                author,
+                locked: preset.locked,
+                allowed_roles,
+            });
+        }
+
+        Ok(GuildImport {
+            settings,
+            presets,
+            notes: remapper.notes,
+        })
+    }
+}
+
+
+/// Convert a [SystemTime] into a unix timestamp.
+fn unix_timestamp(time: SystemTime) -> BobResult<i64> {
+    let secs = time.duration_since(UNIX_EPOCH)
+        .bob_catch(ErrorKind::Developer, "Time is before the unix epoch.")?
+        .as_secs();
+
+    i64::try_from(secs).bob_catch(ErrorKind::Developer, "Time is too far in the future.")
+}
+
+
+/// Remaps the ids of a [GuildExport] to the ones of the guild it is being imported into, taking notes along the way.
+struct Remapper<'a> {
+    http: &'a Http,
+    guild: &'a PartialGuild,
+    /// The guild the export was created from.
+    source_gid: u64,
+    channels: HashMap<ChannelId, GuildChannel>,
+    role_names: HashMap<u64, String>,
+    member_names: HashMap<u64, String>,
+    role_cache: HashMap<u64, Option<PresetTarget>>,
+    missing_roles: Vec<String>,
+    member_cache: HashMap<u64, Option<UserId>>,
+    /// Human-readable notes about the remapping process.
+    notes: Vec<String>,
+}
+
+impl Remapper<'_> {
+    /// Find the channel matching `exported` in the guild, by id or else by name and `kind`.
+    fn channel(&mut self, exported: &ExportedName, kind: Option<ChannelType>, description: &str) -> Option<ChannelId> {
+        if self.channels.contains_key(&ChannelId(exported.id)) {
+            return Some(ChannelId(exported.id));
+        }
+
+        let found = self.channels.values()
+            .find(|c| kind.map_or(true, |k| c.kind == k) && c.name == exported.name)
+            .map(|c| c.id);
+
+        match found {
+            Some(cid) => self.notes.push(format!("🔀 {} `#{}` was remapped to {}.", description, &exported.name, cid.mention())),
+            None => self.notes.push(format!("❌ {} `#{}` doesn't exist in this server, and will be ignored.", description, &exported.name)),
+        }
+        found
+    }
+
+    /// Find the [PresetTarget] matching the exported role `rid`.
+    fn role(&mut self, rid: RoleId) -> Option<PresetTarget> {
+        if let Some(target) = self.role_cache.get(&rid.0) {
+            return target.clone();
+        }
+
+        let target = remap_role(self.guild, self.source_gid, rid, self.role_names.get(&rid.0), &mut self.notes);
+        self.role_cache.insert(rid.0, target.clone());
+        target
+    }
+
+    /// Find the [RoleId] matching the exported role `rid`.
+    fn role_id(&mut self, rid: RoleId) -> Option<RoleId> {
+        match self.role(rid)? {
+            PresetTarget::Everyone => Some(RoleId(self.guild.id.0)),
+            PresetTarget::Role { name } => self.guild.role_by_name(&name).map(|r| r.id),
+            _ => None,
+        }
+    }
+
+    /// Find the [UserId] matching the exported member `uid`.
+    async fn member(&mut self, uid: UserId) -> Option<UserId> {
+        if let Some(found) = self.member_cache.get(&uid.0) {
+            return *found;
+        }
+
+        let found = remap_member(self.http, self.guild, uid, self.member_names.get(&uid.0), &mut self.notes).await;
+        self.member_cache.insert(uid.0, found);
+        found
+    }
+
+    /// Remap the [ExportedSettings] into `settings`, dropping the ones referring to missing channels, roles or members.
+    async fn settings(&mut self, exported: ExportedSettings, settings: &mut GuildSettings) -> BobResult<()> {
+        let category = Some(ChannelType::Category);
+        let text = Some(ChannelType::Text);
+
+        for default in exported.default_presets {
+            match &default.category {
+                None => settings.default_presets.push((None, default.preset.channelify())),
+                Some(c) => if let Some(cid) = self.channel(c, category, "Default preset category") {
+                    settings.default_presets.push((Some(cid), default.preset.channelify()));
+                },
+            }
+        }
+
+        if let Some(er) = exported.event_rooms {
+            let cid = match &er.category {
+                Some(c) => self.channel(c, category, "Event rooms category"),
+                None => None,
+            };
+            settings.event_rooms = Some((er.preset.channelify(), Duration::from_secs(er.lead_time), cid));
+        }
+
+        settings.companion_mode = exported.companion_mode.as_deref()
+            .map(CompanionMode::parse).transpose()
+            .map_err(|_| BobError::from_msg(ErrorKind::User, "The file contains an invalid companion mode."))?;
+
+        settings.archive_channel = match &exported.archive_channel {
+            Some(c) => self.channel(c, text, "Archive channel"),
+            None => None,
+        };
+
+        settings.room_limits = (exported.room_limits.per_member, exported.room_limits.per_category, exported.room_limits.per_guild);
+
+        for rid in exported.cooldown_exemptions {
+            if let Some(rid) = self.role_id(RoleId(rid)) {
+                settings.cooldown_exemptions.push(rid);
+            }
+        }
+
+        for oc in exported.overflow_categories {
+            let from = self.channel(&oc.category, category, "Overflowing category");
+            let into = self.channel(&oc.overflow, category, "Overflow category");
+            if let (Some(from), Some(into)) = (from, into) {
+                settings.overflow_categories.push((from, into));
+            }
+        }
+
+        for bc in exported.build_categories {
+            if let Some(cid) = self.channel(&bc, category, "Build category") {
+                settings.build_categories.push(cid);
+            }
+        }
+
+        for co in exported.category_orderings {
+            let ordering = RoomOrdering::parse(&co.ordering)
+                .map_err(|_| BobError::from_msg(ErrorKind::User, "The file contains an invalid room ordering."))?;
+            let anchor = match &co.anchor {
+                Some(a) => self.channel(a, None, "Anchor channel"),
+                None => None,
+            };
+            if let Some(cid) = self.channel(&co.category, category, "Ordered category") {
+                settings.category_orderings.push((cid, ordering, anchor));
+            }
+        }
+
+        settings.command_visibilities = exported.command_visibilities.into_iter()
+            .map(|cv| (cv.command, cv.ephemeral))
+            .collect();
+
+        for rid in exported.builder_roles {
+            if let Some(rid) = self.role_id(RoleId(rid)) {
+                settings.builder_roles.push(rid);
+            }
+        }
+
+        for ban in exported.build_bans {
+            if let Some(uid) = self.member(UserId(ban.member)).await {
+                let expires_at = ban.expires_at.map(|t| UNIX_EPOCH + Duration::from_secs(u64::try_from(t).unwrap_or(0)));
+                settings.build_bans.push((uid, expires_at, ban.reason));
+            }
+        }
+
+        for pattern in exported.blocked_names {
+            match compile_pattern(&pattern) {
+                Ok(_) => settings.blocked_names.push(pattern),
+                Err(_) => self.notes.push(format!("❌ Blocked name `{}` isn't a valid pattern, and will be ignored.", &pattern)),
+            }
+        }
+
+        settings.moderation_channel = match &exported.moderation_channel {
+            Some(c) => self.channel(c, text, "Moderation channel"),
+            None => None,
+        };
+
+        Ok(())
+    }
+}
+
+
+/// Find the [PresetTarget] in `guild` matching the role `rid` of the guild `source_gid`.
+fn remap_role(guild: &PartialGuild, source_gid: u64, rid: RoleId, name: Option<&String>, notes: &mut Vec<String>) -> Option<PresetTarget> {
+    // @everyone has the same id as its guild
+    if rid.0 == source_gid {
+        return Some(PresetTarget::Everyone);
+    }
+
+    if let Some(role) = guild.roles.get(&rid) {
+        return Some(PresetTarget::Role { name: role.name.clone() });
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => {
+            notes.push(format!("❌ Role `{}` is unknown, and its overwrites will be ignored.", &rid));
+            return None;
+        }
+    };
+
+    match guild.role_by_name(name) {
+        Some(role) => {
+            notes.push(format!("🔀 Role `@{}` was remapped by name.", &name));
+            Some(PresetTarget::Role { name: role.name.clone() })
+        },
+        None => {
+            notes.push(format!("❌ Role `@{}` doesn't exist in this server, and its overwrites will be ignored.", &name));
+            None
+        },
+    }
+}
+
+
+/// Find the [UserId] of the member of `guild` matching the member `uid` of the exported guild.
+async fn remap_member(http: &Http, guild: &PartialGuild, uid: UserId, name: Option<&String>, notes: &mut Vec<String>) -> Option<UserId> {
+    if guild.member(http, uid).await.is_ok() {
+        return Some(uid);
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => {
+            notes.push(format!("❌ Member `{}` is unknown, and its overwrites will be ignored.", &uid));
+            return None;
+        }
+    };
+
+    let found = guild.id.search_members(http, name, Some(10))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .find(|m| &m.user.name == name);
+
+    match found {
+        Some(member) => {
+            notes.push(format!("🔀 Member `{}` was remapped by name.", &name));
+            Some(member.user.id)
+        },
+        None => {
+            notes.push(format!("❌ Member `{}` isn't in this server, and their overwrites will be ignored.", &name));
+            None
+        },
+    }
+}
+
+
+/// A [GuildExport] whose ids have been remapped to the ones of the guild it is being imported into.
+pub struct GuildImport {
+    pub settings: GuildSettings,
+    pub presets: Vec<ImportedPreset>,
+    /// Human-readable notes about the remapping process.
+    pub notes: Vec<String>,
+}
+
+/// Describe each of the given [GuildSettings], or [None] for the ones which aren't set.
+fn describe_settings(settings: &GuildSettings) -> Vec<(&'static str, Option<String>)> {
+    fn list(mut items: Vec<String>) -> Option<String> {
+        items.sort();
+        match items.is_empty() {
+            true => None,
+            false => Some(items.join(", ")),
+        }
+    }
+
+    let (member, category, guild) = settings.room_limits;
+    let limit = |value: Option<u32>| value.map_or_else(|| "none".to_string(), |v| v.to_string());
+
+    vec![
+        ("Command channel", settings.command_channel.map(|c| c.mention().to_string())),
+        ("Deletion time", settings.deletion_time.map(|t| format!("**{} seconds**", t.as_secs()))),
+        ("Default presets", list(settings.default_presets.iter()
+            .map(|(c, p)| match c {
+                Some(c) => format!("`{}` in {}", p, c.mention()),
+                None => format!("`{}`", p),
+            })
+            .collect())),
+        ("Event rooms", settings.event_rooms.as_ref().map(|(p, t, c)| match c {
+            Some(c) => format!("`{}`, {} seconds before, in {}", p, t.as_secs(), c.mention()),
+            None => format!("`{}`, {} seconds before", p, t.as_secs()),
+        })),
+        ("Companion mode", settings.companion_mode.map(|m| m.as_str().to_string())),
+        ("Archive channel", settings.archive_channel.map(|c| c.mention().to_string())),
+        ("Room limits", match settings.room_limits {
+            (None, None, None) => None,
+            _ => Some(format!("member: {}, category: {}, server: {}", limit(member), limit(category), limit(guild))),
+        }),
+        ("Cooldown exemptions", list(settings.cooldown_exemptions.iter().map(|r| r.mention().to_string()).collect())),
+        ("Overflow categories", list(settings.overflow_categories.iter()
+            .map(|(c, o)| format!("{} → {}", c.mention(), o.mention()))
+            .collect())),
+        ("Build categories", list(settings.build_categories.iter().map(|c| c.mention().to_string()).collect())),
+        ("Category orderings", list(settings.category_orderings.iter()
+            .map(|(c, o, a)| match a {
+                Some(a) => format!("{}: {} below {}", c.mention(), o.as_str(), a.mention()),
+                None => format!("{}: {}", c.mention(), o.as_str()),
+            })
+            .collect())),
+        ("Command visibilities", list(settings.command_visibilities.iter()
+            .map(|(c, e)| format!("`{}`: {}", c, if *e { "ephemeral" } else { "public" }))
+            .collect())),
+        ("Builder roles", list(settings.builder_roles.iter().map(|r| r.mention().to_string()).collect())),
+        ("Build bans", list(settings.build_bans.iter()
+            .map(|(u, t, _)| match t.map(unix_timestamp) {
+                Some(Ok(t)) => format!("{} until <t:{}:f>", u.mention(), t),
+                _ => u.mention().to_string(),
+            })
+            .collect())),
+        ("Blocked names", list(settings.blocked_names.iter().map(|p| format!("`{}`", p)).collect())),
+        ("Moderation channel", settings.moderation_channel.map(|c| c.mention().to_string())),
+    ]
+}
+
+/// Describe the locks of a preset, to be appended to its name.
+fn describe_locks(locked: bool, allowed_roles: &[RoleId]) -> String {
+    let mut result = String::new();
+    if locked {
+        result.push_str(" 🔒");
+    }
+    if !allowed_roles.is_empty() {
+        let roles: Vec<String> = allowed_roles.iter().map(|r| r.mention().to_string()).collect();
+        result.push_str(&format!(" (only {})", roles.join(", ")));
+    }
+    result
+}
+
+impl GuildImport {
+    /// Ensure that the given [Member] is allowed to import the presets: overwriting an existing preset requires being
+    /// allowed to change it, and locking or restricting a preset requires the **Manage Channels** permission.
+    pub fn check_presets(&self, guild_id: GuildId, member: &Member) -> BobResult<()> {
+        let permissions = member.permissions
+            .bob_catch(ErrorKind::External, "Interaction didn't have the Member's Permissions")?;
+
+        for preset in self.presets.iter() {
+            if let Some(current) = guild_id.get_preset(&preset.name)? {
+                current.check_edit(member)?;
+            }
+
+            if (preset.locked || !preset.allowed_roles.is_empty()) && !permissions.manage_channels() {
+                return Err(BobError::from_msg(ErrorKind::User, &format!(
+                    "Preset `{}` is locked or restricted: you need to have **Manage Channels** permission on the guild to import it.",
+                    &preset.name
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Describe the changes that [GuildImport::apply] would make to the given [GuildId].
+    pub fn diff(&self, guild_id: GuildId) -> BobResult<Vec<String>> {
+        let mut result = vec![];
+
+        let current = describe_settings(&guild_id.get_guild_settings()?);
+        let new = describe_settings(&self.settings);
+
+        for ((name, current), (_, new)) in current.into_iter().zip(new) {
+            match (current, new) {
+                (None, None) => {},
+                (Some(current), Some(new)) if current == new => result.push(format!("➖ {}: {} (unchanged)", name, new)),
+                (Some(current), Some(new)) => result.push(format!("✏️ {}: {} → {}", name, current, new)),
+                (None, Some(new)) => result.push(format!("➕ {}: {}", name, new)),
+                (Some(current), None) => result.push(format!("🗑 {}: {}", name, current)),
+            }
+        }
+
+        for preset in &self.presets {
+            let name = &preset.name;
+            let locks = describe_locks(preset.locked, &preset.allowed_roles);
+
+            match guild_id.get_preset(name)? {
+                None => result.push(format!("➕ Preset `{}`{}", name, locks)),
+                Some(current) => {
+                    let unchanged = serde_json::to_value(current.data()?).ok() == serde_json::to_value(&preset.data).ok()
+                        && current.author()? == preset.author
+                        && current.locked == preset.locked
+                        && current.allowed_roles()? == preset.allowed_roles;
+
+                    match unchanged {
+                        true => result.push(format!("➖ Preset `{}`{} (unchanged)", name, locks)),
+                        false => result.push(format!("✏️ Preset `{}`{}", name, locks)),
+                    }
+                },
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Apply the [GuildImport] to the given [GuildId] in a single transaction, replacing its settings and overwriting
+    /// the existing presets with the same names.
+    pub fn apply(self, guild_id: GuildId) -> BobResult<()> {
+        guild_id.replace_guild_settings(&self.settings, &self.presets)
+    }
+}
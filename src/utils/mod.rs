@@ -1,4 +1,9 @@
 pub mod permission_overwrites;
+pub mod overwrite_composition;
 pub mod discord_display;
 pub mod channel_names;
-pub mod command_router;
\ No newline at end of file
+pub mod command_router;
+pub mod guild_export;
+pub mod cooldowns;
+pub mod doctor;
+pub mod moderation;
@@ -0,0 +1,185 @@
+//! This module contains the moderation checks applied to the channels built by Bob.
+
+use regex::{Regex, RegexBuilder};
+use serenity::model::prelude::*;
+use serenity::prelude::{Context};
+use crate::errors::*;
+use crate::database::models::{WithBuildBans, WithNameBlocklist, WithModerationChannel, MayHaveBeenCreatedByBob};
+
+
+/// Compile a pattern of the name blocklist into a case-insensitive [Regex].
+pub fn compile_pattern(pattern: &str) -> BobResult<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .bob_catch(ErrorKind::User, "Invalid pattern.")
+}
+
+
+/// Find the first of the given `patterns` matched by the given channel name.
+///
+/// Patterns which somehow became invalid are skipped.
+fn first_matching_pattern(patterns: Vec<String>, name: &str) -> Option<String> {
+    for pattern in patterns {
+        match compile_pattern(&pattern) {
+            Ok(regex) if regex.is_match(name) => return Some(pattern),
+            Ok(_) => {},
+            Err(e) => warn!("Skipping blocked name pattern {:?}: {}", &pattern, &e),
+        }
+    }
+
+    None
+}
+
+
+/// Find the first pattern of the name blocklist of the guild matched by the given channel name.
+pub fn find_blocked_pattern(guild_id: GuildId, name: &str) -> BobResult<Option<String>> {
+    Ok(first_matching_pattern(guild_id.get_name_blocklist()?, name))
+}
+
+
+/// Send the given message to the moderation channel of the guild, if one has been set.
+pub async fn log_moderation(ctx: &Context, guild_id: GuildId, content: &str) -> BobResult<()> {
+    let channel = match guild_id.get_moderation_channel()? {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+
+    channel.send_message(&ctx.http, |m| m
+        .content(content)
+        .allowed_mentions(|am| am.empty_parse())
+    ).await.bob_catch(ErrorKind::Admin, "Couldn't send a message to the moderation channel.")?;
+
+    Ok(())
+}
+
+
+/// Ensure that the given [Member] isn't banned from building channels.
+fn check_build_ban(guild_id: GuildId, member: &Member) -> BobResult<()> {
+    let ban = match guild_id.get_build_ban(member.user.id)? {
+        Some(ban) => ban,
+        None => return Ok(()),
+    };
+
+    let until = match ban.expires_at {
+        Some(expires_at) => format!(" until <t:{}:f>", expires_at),
+        None => "".to_string(),
+    };
+    let reason = match &ban.reason {
+        Some(reason) => format!(" Reason: {}", reason),
+        None => "".to_string(),
+    };
+
+    Err(BobError::from_msg(ErrorKind::User, &format!(
+        "You've been banned from building channels in this server{}.{}", until, reason
+    )))
+}
+
+
+/// Ensure that the given channel name doesn't match any pattern of the name blocklist.
+fn check_name(guild_id: GuildId, name: &str) -> BobResult<()> {
+    match find_blocked_pattern(guild_id, name)? {
+        Some(_) => Err(BobError::from_msg(ErrorKind::User, &format!("The name _#{}_ isn't allowed in this server.", name))),
+        None => Ok(()),
+    }
+}
+
+
+/// _To be run before building a channel._
+///
+/// Ensure that the given [Member] can build a channel with the given `name`, logging the rejection in the moderation
+/// channel if they can't.
+pub async fn moderate_build(ctx: &Context, guild_id: GuildId, member: &Member, name: &str) -> BobResult<()> {
+    let result = check_build_ban(guild_id, member).and_then(|_| check_name(guild_id, name));
+
+    if let Err(e) = &result {
+        let content = format!(
+            "🛡 Rejected the build of _#{}_ by {}: {}",
+            name,
+            member.mention(),
+            e.msg.as_deref().unwrap_or("_no message_"),
+        );
+        if let Err(le) = log_moderation(ctx, guild_id, &content).await {
+            warn!("{}", &le);
+        }
+    }
+
+    result
+}
+
+
+/// _To be run in a `channel_update` event._
+///
+/// If a channel built by Bob was renamed to a name matching the name blocklist of its guild, revert the rename and log
+/// it in the moderation channel.
+///
+/// # Returns
+///
+/// - `Ok(true)` if the rename was reverted.
+/// - `Ok(false)` if the rename was allowed.
+/// - `Err(_)` if an error occurred.
+pub async fn moderate_rename(ctx: &Context, old: &Option<Channel>, new: &Channel) -> BobResult<bool> {
+    let (old, new) = match (old.as_ref().and_then(|c| c.clone().guild()), new.clone().guild()) {
+        (Some(old), Some(new)) => (old, new),
+        _ => return Ok(false),
+    };
+
+    if old.name == new.name || !new.was_created_by_bob()? {
+        return Ok(false);
+    }
+
+    if find_blocked_pattern(new.guild_id, &new.name)?.is_none() {
+        return Ok(false);
+    }
+
+    new.id.edit(&ctx.http, |c| c.name(&old.name))
+        .await.bob_catch(ErrorKind::Admin, "Couldn't revert the rename of the channel.")?;
+
+    let owner = match new.get_owner()? {
+        Some(owner) => owner.mention().to_string(),
+        None => "_unknown_".to_string(),
+    };
+    log_moderation(ctx, new.guild_id, &format!(
+        "🛡 Reverted the rename of {} to _#{}_, built by {}.", new.id.mention(), &new.name, owner
+    )).await?;
+
+    Ok(true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn compile_pattern_is_case_insensitive() {
+        let regex = compile_pattern("bad-?word").unwrap();
+        assert!(regex.is_match("a-BAD-Word-room"));
+        assert!(regex.is_match("badword"));
+        assert!(!regex.is_match("bad-room"));
+    }
+
+    #[test]
+    fn compile_pattern_rejects_invalid_patterns() {
+        assert!(compile_pattern("(unclosed").is_err());
+        assert!(compile_pattern("[z-a]").is_err());
+    }
+
+    #[test]
+    fn first_matching_pattern_in_order() {
+        let blocklist = patterns(&["spam", "^bad", "word$"]);
+        assert_eq!(first_matching_pattern(blocklist.clone(), "bad-word"), Some("^bad".to_string()));
+        assert_eq!(first_matching_pattern(blocklist.clone(), "SPAM-word"), Some("spam".to_string()));
+        assert_eq!(first_matching_pattern(blocklist, "not-bad"), None);
+    }
+
+    #[test]
+    fn first_matching_pattern_skips_invalid_patterns() {
+        let blocklist = patterns(&["(unclosed", "room"]);
+        assert_eq!(first_matching_pattern(blocklist, "(unclosed-room"), Some("room".to_string()));
+    }
+}
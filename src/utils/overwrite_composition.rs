@@ -0,0 +1,142 @@
+//! This module contains a builder which composes layers of [PermissionOverwrite]s into a single overwrite per target.
+
+use serenity::model::prelude::{PermissionOverwrite};
+
+
+/// Composes layers of [PermissionOverwrite]s, keeping a single overwrite for each target.
+///
+/// Layers are added from the lowest to the highest precedence: when two layers have an overwrite for the same target,
+/// the bits explicitly allowed or denied by the higher layer win, while the bits it doesn't mention are kept from the
+/// lower one.
+#[derive(Debug, Clone, Default)]
+pub struct OverwriteComposer {
+    /// The composed overwrites, in the order their targets first appeared.
+    overwrites: Vec<PermissionOverwrite>,
+}
+
+impl OverwriteComposer {
+    /// Create an empty [OverwriteComposer].
+    pub fn new() -> Self {
+        OverwriteComposer::default()
+    }
+
+    /// Add a single [PermissionOverwrite] on top of the current ones.
+    pub fn layer(&mut self, overwrite: PermissionOverwrite) -> &mut Self {
+        match self.overwrites.iter_mut().find(|o| o.kind == overwrite.kind) {
+            Some(current) => {
+                current.allow = (current.allow - overwrite.deny) | overwrite.allow;
+                current.deny = (current.deny - overwrite.allow) | overwrite.deny;
+            },
+            None => self.overwrites.push(overwrite),
+        }
+        self
+    }
+
+    /// Add a layer of [PermissionOverwrite]s on top of the current ones.
+    ///
+    /// Overwrites in the same layer are applied in order.
+    pub fn layer_all<I: IntoIterator<Item=PermissionOverwrite>>(&mut self, overwrites: I) -> &mut Self {
+        for overwrite in overwrites {
+            self.layer(overwrite);
+        }
+        self
+    }
+
+    /// Get the composed [PermissionOverwrite]s, one for each target.
+    pub fn compose(self) -> Vec<PermissionOverwrite> {
+        self.overwrites
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serenity::model::prelude::{PermissionOverwriteType, Permissions, RoleId, UserId};
+
+    fn get(composer: &OverwriteComposer, kind: PermissionOverwriteType) -> PermissionOverwrite {
+        composer.clone().compose().into_iter().find(|o| o.kind == kind).unwrap()
+    }
+
+    fn overwrite(kind: PermissionOverwriteType, allow: Permissions, deny: Permissions) -> PermissionOverwrite {
+        PermissionOverwrite { allow, deny, kind }
+    }
+
+    const EVERYONE: PermissionOverwriteType = PermissionOverwriteType::Role(RoleId(1));
+    const MEMBER: PermissionOverwriteType = PermissionOverwriteType::Member(UserId(2));
+
+    #[test]
+    fn distinct_targets_are_kept_in_order() {
+        let mut composer = OverwriteComposer::new();
+        composer.layer(overwrite(EVERYONE, Permissions::empty(), Permissions::CONNECT));
+        composer.layer(overwrite(MEMBER, Permissions::CONNECT, Permissions::empty()));
+
+        let result = composer.compose();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].kind, EVERYONE);
+        assert_eq!(result[1].kind, MEMBER);
+    }
+
+    #[test]
+    fn same_target_is_merged() {
+        let mut composer = OverwriteComposer::new();
+        composer.layer(overwrite(MEMBER, Permissions::SPEAK, Permissions::empty()));
+        composer.layer(overwrite(MEMBER, Permissions::CONNECT, Permissions::empty()));
+
+        let result = composer.compose();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].allow, Permissions::SPEAK | Permissions::CONNECT);
+        assert_eq!(result[0].deny, Permissions::empty());
+    }
+
+    #[test]
+    fn higher_allow_overrides_lower_deny() {
+        let mut composer = OverwriteComposer::new();
+        composer.layer(overwrite(EVERYONE, Permissions::empty(), Permissions::VIEW_CHANNEL | Permissions::CONNECT));
+        composer.layer(overwrite(EVERYONE, Permissions::CONNECT, Permissions::empty()));
+
+        let result = get(&composer, EVERYONE);
+        assert_eq!(result.allow, Permissions::CONNECT);
+        assert_eq!(result.deny, Permissions::VIEW_CHANNEL);
+    }
+
+    #[test]
+    fn higher_deny_overrides_lower_allow() {
+        let mut composer = OverwriteComposer::new();
+        composer.layer(overwrite(EVERYONE, Permissions::VIEW_CHANNEL | Permissions::CONNECT, Permissions::empty()));
+        composer.layer(overwrite(EVERYONE, Permissions::empty(), Permissions::CONNECT));
+
+        let result = get(&composer, EVERYONE);
+        assert_eq!(result.allow, Permissions::VIEW_CHANNEL);
+        assert_eq!(result.deny, Permissions::CONNECT);
+    }
+
+    #[test]
+    fn unmentioned_bits_are_kept() {
+        let mut composer = OverwriteComposer::new();
+        composer.layer(overwrite(MEMBER, Permissions::SPEAK, Permissions::STREAM));
+        composer.layer(overwrite(MEMBER, Permissions::empty(), Permissions::empty()));
+
+        let result = get(&composer, MEMBER);
+        assert_eq!(result.allow, Permissions::SPEAK);
+        assert_eq!(result.deny, Permissions::STREAM);
+    }
+
+    #[test]
+    fn layers_are_applied_in_order() {
+        let mut composer = OverwriteComposer::new();
+        composer.layer_all(vec![
+            overwrite(EVERYONE, Permissions::CONNECT, Permissions::empty()),
+            overwrite(MEMBER, Permissions::empty(), Permissions::SPEAK),
+        ]);
+        composer.layer_all(vec![
+            overwrite(EVERYONE, Permissions::empty(), Permissions::CONNECT),
+            overwrite(MEMBER, Permissions::SPEAK, Permissions::empty()),
+        ]);
+
+        assert_eq!(get(&composer, EVERYONE).deny, Permissions::CONNECT);
+        assert_eq!(get(&composer, EVERYONE).allow, Permissions::empty());
+        assert_eq!(get(&composer, MEMBER).allow, Permissions::SPEAK);
+        assert_eq!(get(&composer, MEMBER).deny, Permissions::empty());
+    }
+}
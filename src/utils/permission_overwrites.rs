@@ -1,92 +1,467 @@
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 use crate::errors::*;
+use crate::extensions::*;
 use crate::database::models::{PresetData};
+use crate::utils::overwrite_composition::{OverwriteComposer};
 
 
 const ALL_VOICE_PERMISSIONS: u64 = 298845201;
 
 
-/// Create a [PermissionOverwrite] which allows all [Permissions].
-fn allow_all(kind: PermissionOverwriteType) -> PermissionOverwrite {
+/// Create a [PermissionOverwrite] which allows all the voice [Permissions] that are also `grantable`.
+fn allow_all(kind: PermissionOverwriteType, grantable: Permissions) -> PermissionOverwrite {
     PermissionOverwrite {
-        allow: Permissions::from_bits(ALL_VOICE_PERMISSIONS).unwrap(),
+        allow: Permissions::from_bits(ALL_VOICE_PERMISSIONS).unwrap() & grantable,
         deny: Permissions::empty(),
         kind,
     }
 }
 
 
-/// Create a [PermissionOverwrite] which denies all [Permissions].
-fn deny_all(kind: PermissionOverwriteType) -> PermissionOverwrite {
-    PermissionOverwrite {
-        allow: Permissions::all(),
-        deny: Permissions::from_bits(ALL_VOICE_PERMISSIONS).unwrap(),
-        kind,
+/// Create a [PermissionOverwrite] which allows all the `grantable` voice [Permissions] for the given [UserId].
+fn owner(user_id: UserId, grantable: Permissions) -> PermissionOverwrite {
+    allow_all(PermissionOverwriteType::Member(user_id), grantable)
+}
+
+
+/// Compute the [Permissions] the given [Member] would have in a channel of the [PartialGuild] with the given
+/// [PermissionOverwrite]s.
+pub fn effective_permissions(guild: &PartialGuild, member: &Member, overwrites: &[PermissionOverwrite]) -> Permissions {
+    if member.user.id == guild.owner_id {
+        return Permissions::all();
     }
+
+    let everyone = RoleId(guild.id.0);
+    let mut permissions = std::iter::once(everyone)
+        .chain(member.roles.iter().copied())
+        .filter_map(|r| guild.roles.get(&r))
+        .fold(Permissions::empty(), |p, r| p | r.permissions);
+
+    if permissions.administrator() {
+        return Permissions::all();
+    }
+
+    let mut apply = |matches: &dyn Fn(&PermissionOverwriteType) -> bool| {
+        let (allow, deny) = overwrites.iter()
+            .filter(|o| matches(&o.kind))
+            .fold((Permissions::empty(), Permissions::empty()), |(a, d), o| (a | o.allow, d | o.deny));
+        permissions = (permissions - deny) | allow;
+    };
+
+    apply(&|k| k == &PermissionOverwriteType::Role(everyone));
+    apply(&|k| matches!(k, PermissionOverwriteType::Role(r) if r != &everyone && member.roles.contains(r)));
+    apply(&|k| k == &PermissionOverwriteType::Member(member.user.id));
+
+    permissions
 }
 
 
-/// Create a [PermissionOverwrite] with no [Permissions].
-fn empty(kind: PermissionOverwriteType) -> PermissionOverwrite {
-    PermissionOverwrite {
-        allow: Permissions::empty(),
-        deny: Permissions::empty(),
-        kind,
+/// Compute the [Permissions] Bob can put in the overwrites of a channel with the given [PermissionOverwrite]s.
+///
+/// Discord only allows non-Administrators to grant permissions they have themselves, and never the
+/// __Manage Permissions__ one.
+fn grantable_permissions(guild: &PartialGuild, bob: &Member, overwrites: &[PermissionOverwrite]) -> Permissions {
+    let permissions = effective_permissions(guild, bob, overwrites);
+
+    match permissions.administrator() {
+        true => Permissions::all(),
+        false => permissions - Permissions::MANAGE_ROLES,
     }
 }
 
 
-/// Create a [PermissionOverwrite] which allows all [Permissions] for the given [UserId].
-fn owner(user_id: UserId) -> PermissionOverwrite {
-    allow_all(PermissionOverwriteType::Member(user_id))
+/// Compute the [Permissions] Bob can put in the overwrites of a channel placed in the category with the given
+/// [ChannelId], or outside of any category if [None].
+pub async fn fetch_grantable_permissions(ctx: &Context, guild: &PartialGuild, category_id: Option<ChannelId>) -> BobResult<Permissions> {
+    let overwrites = match category_id {
+        None => vec![],
+        Some(category_id) => category_id
+            .to_channel(ctx)
+            .await
+            .bob_catch(ErrorKind::External, "Couldn't retrieve channel info")?
+            .category()
+            .bob_catch(ErrorKind::Developer, "Channel isn't a ChannelCategory")?
+            .permission_overwrites,
+    };
+
+    let bob = guild.ext_member(&ctx.http, ctx.cache.current_user().id).await?;
+    Ok(grantable_permissions(guild, &bob, &overwrites))
+}
+
+
+/// Remove the [Permissions] which aren't `grantable` from the given [PermissionOverwrite]s.
+///
+/// # Returns
+///
+/// The [Permissions] which were removed from at least one of the overwrites.
+pub fn restrict(permows: &mut [PermissionOverwrite], grantable: Permissions) -> Permissions {
+    let mut removed = Permissions::empty();
+
+    for permow in permows.iter_mut() {
+        removed |= (permow.allow | permow.deny) - grantable;
+        permow.allow &= grantable;
+        permow.deny &= grantable;
+    }
+
+    removed
+}
+
+
+/// The [Permissions] needed to see and join a channel.
+fn join_permissions() -> Permissions {
+    Permissions::VIEW_CHANNEL | Permissions::CONNECT
+}
+
+
+/// Who should be able to join a channel, in addition to what its category and preset allow.
+#[derive(Debug, Clone, Default)]
+pub struct RoomAccess {
+    /// Prevent @everyone from seeing and joining the channel.
+    pub private: bool,
+    /// The members and roles which should be able to see and join the channel.
+    pub invitees: Vec<PermissionOverwriteType>,
+}
+
+impl RoomAccess {
+    /// Create the [PermissionOverwrite]s granting the configured access to a channel of the given [GuildId].
+    fn permission_overwrites(&self, guild_id: GuildId) -> Vec<PermissionOverwrite> {
+        let mut result = vec![];
+
+        if self.private {
+            result.push(PermissionOverwrite {
+                allow: Permissions::empty(),
+                deny: join_permissions(),
+                kind: PermissionOverwriteType::Role(RoleId(guild_id.0)),
+            });
+        }
+
+        for invitee in self.invitees.iter() {
+            result.push(PermissionOverwrite {
+                allow: join_permissions(),
+                deny: Permissions::empty(),
+                kind: *invitee,
+            });
+        }
+
+        result
+    }
 }
 
 
 pub struct ChannelBuilderPermissionOverwrites {
+    /// The [Permissions] which couldn't be granted to the creator or put in the other overwrites, as Bob doesn't have
+    /// them.
+    ungranted: Permissions,
     own_permow: PermissionOverwrite,
     creator_permow: PermissionOverwrite,
     category_permows: Vec<PermissionOverwrite>,
     preset_permows: Vec<PermissionOverwrite>,
+    access_permows: Vec<PermissionOverwrite>,
 }
 
 impl ChannelBuilderPermissionOverwrites {
-    /// Merge all [PermissionOverwrite]s into a single [Vec].
+    /// Get the [Permissions] which couldn't be granted to the creator or put in the other overwrites, as Bob doesn't
+    /// have them.
+    pub fn ungranted(&self) -> Permissions {
+        self.ungranted
+    }
+
+    /// Compose all [PermissionOverwrite]s into a single [Vec], with one overwrite for each target.
+    ///
+    /// The overwrites are layered from the lowest to the highest precedence, with the bits set by a higher layer
+    /// overriding the ones set by a lower layer for the same target:
+    /// 1. the overwrites of the category;
+    /// 2. the overwrites of the preset;
+    /// 3. the overwrites of the [RoomAccess];
+    /// 4. the overwrite of the creator;
+    /// 5. the overwrite of Bob.
     pub fn merge(self) -> Vec<PermissionOverwrite> {
-        let mut current = self;
-        let mut result = vec![];
+        let mut composer = OverwriteComposer::new();
 
-        result.append(&mut current.category_permows);
-        result.append(&mut current.preset_permows);
-        result.push(current.creator_permow);
-        result.push(current.own_permow);
+        composer
+            .layer_all(self.category_permows)
+            .layer_all(self.preset_permows)
+            .layer_all(self.access_permows)
+            .layer(self.creator_permow)
+            .layer(self.own_permow);
 
-        result
+        composer.compose()
     }
 
     /// Create a [ChannelBuilderPermissionOverwrites] by creating manually the permission overwrites.
-    pub fn build(own_id: UserId, creator_id: UserId, category: Option<ChannelCategory>, preset: Option<PresetData>) -> Self {
+    ///
+    /// Bob and the creator are only granted the voice permissions which are `grantable`, and the permissions which
+    /// aren't `grantable` are left out of every other overwrite.
+    pub fn build(guild: &PartialGuild, own_id: UserId, creator_id: UserId, category: Option<ChannelCategory>, preset: Option<PresetData>, access: &RoomAccess, grantable: Permissions) -> Self {
+        let mut category_permows = match category {
+            Some(category) => category.permission_overwrites,
+            None => vec![],
+        };
+        let mut preset_permows = match preset {
+            Some(preset) => preset.permission_overwrites(guild, creator_id),
+            None => vec![],
+        };
+        let mut access_permows = access.permission_overwrites(guild.id);
+
+        let ungranted = (Permissions::from_bits(ALL_VOICE_PERMISSIONS).unwrap() - grantable)
+            | restrict(&mut category_permows, grantable)
+            | restrict(&mut preset_permows, grantable)
+            | restrict(&mut access_permows, grantable);
+
         ChannelBuilderPermissionOverwrites {
-            own_permow: owner(own_id),
-            creator_permow: owner(creator_id),
-            category_permows: match category {
-                Some(category) => category.permission_overwrites,
-                None => vec![],
-            },
-            preset_permows: match preset {
-                Some(preset) => preset.permissions,
-                None => vec![],
-            },
+            ungranted,
+            own_permow: owner(own_id, grantable),
+            creator_permow: owner(creator_id, grantable),
+            category_permows,
+            preset_permows,
+            access_permows,
         }
     }
 
     /// Create a [ChannelBuilderPermissionOverwrites] by retrieving permission overwrites from some common command structs.
-    pub async fn fetch(ctx: &Context, creator: &Member, category: &Option<ChannelCategory>, preset: Option<PresetData>) -> BobResult<Self> {
+    ///
+    /// The channel inherits the overwrites of the given `category`, but the permissions Bob can grant depend on the
+    /// category it is placed in, which may be an overflow category.
+    pub async fn fetch(ctx: &Context, guild: &PartialGuild, creator: &Member, category: &Option<ChannelCategory>, placement: Option<ChannelId>, preset: &Option<PresetData>, access: &RoomAccess) -> BobResult<Self> {
         let own_id = ctx.cache.current_user().id.to_owned();
         let creator_id = creator.user.id.to_owned();
         let category = category.to_owned();
-        let preset = preset; // FIXME: This can't be converted into owned for some weird reason?
+        let preset = preset.to_owned();
+
+        let grantable = fetch_grantable_permissions(ctx, guild, placement).await?;
+
+        Ok(ChannelBuilderPermissionOverwrites::build(guild, own_id, creator_id, category, preset, access, grantable))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json};
+
+    const GUILD: GuildId = GuildId(1);
+    const EVERYONE: PermissionOverwriteType = PermissionOverwriteType::Role(RoleId(1));
+    const ROLE: PermissionOverwriteType = PermissionOverwriteType::Role(RoleId(2));
+    const CREATOR: UserId = UserId(3);
+    const BOB: UserId = UserId(4);
+
+    fn voice() -> Permissions {
+        Permissions::from_bits(ALL_VOICE_PERMISSIONS).unwrap()
+    }
+
+    fn builder(category: Vec<PermissionOverwrite>, preset: Vec<PermissionOverwrite>, access: &RoomAccess) -> ChannelBuilderPermissionOverwrites {
+        ChannelBuilderPermissionOverwrites {
+            ungranted: Permissions::empty(),
+            own_permow: owner(BOB, Permissions::all()),
+            creator_permow: owner(CREATOR, Permissions::all()),
+            category_permows: category,
+            preset_permows: preset,
+            access_permows: access.permission_overwrites(GUILD),
+        }
+    }
+
+    fn guild(roles: &[(RoleId, Permissions)]) -> PartialGuild {
+        let roles: Vec<_> = roles.iter().map(|(id, permissions)| json!({
+            "id": id.to_string(),
+            "name": "",
+            "color": 0,
+            "hoist": false,
+            "managed": false,
+            "mentionable": false,
+            "permissions": permissions.bits().to_string(),
+            "position": 0,
+        })).collect();
+
+        serde_json::from_value(json!({
+            "id": GUILD.to_string(),
+            "name": "",
+            "owner_id": "5",
+            "afk_timeout": 300,
+            "default_message_notifications": 0,
+            "emojis": [],
+            "features": [],
+            "mfa_level": 0,
+            "verification_level": 0,
+            "nsfw_level": 0,
+            "system_channel_flags": 0,
+            "premium_subscription_count": 0,
+            "stickers": [],
+            "roles": roles,
+        })).unwrap()
+    }
+
+    fn member(user_id: UserId, roles: &[RoleId]) -> Member {
+        serde_json::from_value(json!({
+            "user": {"id": user_id.to_string(), "username": "", "discriminator": "0001", "avatar": null},
+            "roles": roles.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+            "guild_id": GUILD.to_string(),
+            "joined_at": null,
+            "deaf": false,
+            "mute": false,
+        })).unwrap()
+    }
+
+    fn find(permows: &[PermissionOverwrite], kind: PermissionOverwriteType) -> &PermissionOverwrite {
+        permows.iter().find(|p| p.kind == kind).unwrap()
+    }
+
+    #[test]
+    fn allow_all_only_allows() {
+        let permow = allow_all(EVERYONE, Permissions::all());
+        assert_eq!(permow.allow, voice());
+        assert_eq!(permow.deny, Permissions::empty());
+    }
+
+    #[test]
+    fn owner_is_limited_to_grantable() {
+        let grantable = Permissions::CONNECT | Permissions::SPEAK | Permissions::MUTE_MEMBERS;
+        let permow = owner(CREATOR, grantable);
+        assert_eq!(permow.kind, PermissionOverwriteType::Member(CREATOR));
+        assert_eq!(permow.allow, Permissions::CONNECT | Permissions::MUTE_MEMBERS);
+    }
+
+    #[test]
+    fn restrict_removes_ungrantable_permissions() {
+        let mut permows = vec![
+            PermissionOverwrite { allow: Permissions::CONNECT | Permissions::MANAGE_ROLES, deny: Permissions::empty(), kind: EVERYONE },
+            PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::SPEAK | Permissions::STREAM, kind: ROLE },
+        ];
+        let removed = restrict(&mut permows, Permissions::CONNECT | Permissions::SPEAK);
+
+        assert_eq!(removed, Permissions::MANAGE_ROLES | Permissions::STREAM);
+        assert_eq!(find(&permows, EVERYONE).allow, Permissions::CONNECT);
+        assert_eq!(find(&permows, ROLE).deny, Permissions::SPEAK);
+    }
+
+    #[test]
+    fn build_restricts_every_overwrite() {
+        let guild = guild(&[]);
+        let mut category: ChannelCategory = serde_json::from_value(json!({
+            "id": "6",
+            "guild_id": GUILD.to_string(),
+            "type": 4,
+            "name": "",
+            "position": 0,
+            "nsfw": false,
+            "permission_overwrites": [],
+        })).unwrap();
+        category.permission_overwrites = vec![PermissionOverwrite { allow: Permissions::STREAM, deny: Permissions::empty(), kind: ROLE }];
+        let access = RoomAccess { private: true, invitees: vec![] };
+        let grantable = join_permissions() | Permissions::MUTE_MEMBERS;
+        let builder = ChannelBuilderPermissionOverwrites::build(&guild, BOB, CREATOR, Some(category), None, &access, grantable);
+
+        assert!(builder.ungranted().contains(Permissions::STREAM));
+        assert!(!builder.ungranted().contains(Permissions::MUTE_MEMBERS));
+
+        let permows = builder.merge();
+        assert_eq!(find(&permows, ROLE).allow, Permissions::empty());
+        assert_eq!(find(&permows, EVERYONE).deny, join_permissions());
+        assert_eq!(find(&permows, PermissionOverwriteType::Member(CREATOR)).allow, join_permissions() | Permissions::MUTE_MEMBERS);
+    }
+
+    #[test]
+    fn effective_permissions_apply_overwrites_in_order() {
+        let guild = guild(&[(RoleId(1), join_permissions()), (RoleId(2), Permissions::SPEAK)]);
+        let member = member(CREATOR, &[RoleId(2)]);
+        let overwrites = vec![
+            PermissionOverwrite { allow: Permissions::STREAM, deny: Permissions::CONNECT, kind: EVERYONE },
+            PermissionOverwrite { allow: Permissions::CONNECT, deny: Permissions::STREAM, kind: ROLE },
+            PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::SPEAK, kind: PermissionOverwriteType::Member(CREATOR) },
+        ];
+
+        assert_eq!(effective_permissions(&guild, &member, &[]), join_permissions() | Permissions::SPEAK);
+        assert_eq!(effective_permissions(&guild, &member, &overwrites), join_permissions());
+    }
+
+    #[test]
+    fn effective_permissions_of_owner_and_administrators() {
+        let guild = guild(&[(RoleId(1), Permissions::empty()), (RoleId(2), Permissions::ADMINISTRATOR)]);
+        let overwrites = vec![PermissionOverwrite { allow: Permissions::empty(), deny: voice(), kind: EVERYONE }];
+
+        assert_eq!(effective_permissions(&guild, &member(UserId(5), &[]), &overwrites), Permissions::all());
+        assert_eq!(effective_permissions(&guild, &member(CREATOR, &[RoleId(2)]), &overwrites), Permissions::all());
+        assert_eq!(effective_permissions(&guild, &member(CREATOR, &[]), &overwrites), Permissions::empty());
+    }
+
+    #[test]
+    fn grantable_permissions_exclude_manage_roles() {
+        let guild = guild(&[(RoleId(1), join_permissions() | Permissions::MANAGE_ROLES), (RoleId(2), Permissions::ADMINISTRATOR)]);
+        let overwrites = vec![PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::CONNECT, kind: EVERYONE }];
+
+        assert_eq!(grantable_permissions(&guild, &member(BOB, &[]), &overwrites), Permissions::VIEW_CHANNEL);
+        assert_eq!(grantable_permissions(&guild, &member(BOB, &[RoleId(2)]), &overwrites), Permissions::all());
+    }
+
+    #[test]
+    fn private_access_denies_everyone_and_allows_invitees() {
+        let access = RoomAccess { private: true, invitees: vec![ROLE] };
+        let permows = access.permission_overwrites(GUILD);
+        assert_eq!(find(&permows, EVERYONE).deny, join_permissions());
+        assert_eq!(find(&permows, ROLE).allow, join_permissions());
+    }
+
+    #[test]
+    fn merge_keeps_one_overwrite_per_target() {
+        let category = vec![PermissionOverwrite { allow: Permissions::CONNECT, deny: Permissions::empty(), kind: EVERYONE }];
+        let preset = vec![PermissionOverwrite { allow: Permissions::SPEAK, deny: Permissions::empty(), kind: EVERYONE }];
+        let permows = builder(category, preset, &RoomAccess::default()).merge();
+
+        assert_eq!(permows.iter().filter(|p| p.kind == EVERYONE).count(), 1);
+        assert_eq!(find(&permows, EVERYONE).allow, Permissions::CONNECT | Permissions::SPEAK);
+    }
+
+    #[test]
+    fn preset_overrides_category() {
+        let category = vec![PermissionOverwrite { allow: Permissions::CONNECT, deny: Permissions::empty(), kind: ROLE }];
+        let preset = vec![PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::CONNECT, kind: ROLE }];
+        let permows = builder(category, preset, &RoomAccess::default()).merge();
+
+        assert_eq!(find(&permows, ROLE).allow, Permissions::empty());
+        assert_eq!(find(&permows, ROLE).deny, Permissions::CONNECT);
+    }
+
+    #[test]
+    fn access_overrides_preset() {
+        let preset = vec![
+            PermissionOverwrite { allow: join_permissions() | Permissions::SPEAK, deny: Permissions::empty(), kind: EVERYONE },
+            PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::CONNECT, kind: ROLE },
+        ];
+        let access = RoomAccess { private: true, invitees: vec![ROLE] };
+        let permows = builder(vec![], preset, &access).merge();
+
+        assert_eq!(find(&permows, EVERYONE).allow, Permissions::SPEAK);
+        assert_eq!(find(&permows, EVERYONE).deny, join_permissions());
+        assert_eq!(find(&permows, ROLE).allow, join_permissions());
+        assert_eq!(find(&permows, ROLE).deny, Permissions::empty());
+    }
+
+    #[test]
+    fn creator_overrides_everything_else() {
+        let creator = PermissionOverwriteType::Member(CREATOR);
+        let preset = vec![PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::CONNECT, kind: creator }];
+        let permows = builder(vec![], preset, &RoomAccess::default()).merge();
+
+        assert_eq!(find(&permows, creator).allow, voice());
+        assert_eq!(find(&permows, creator).deny, Permissions::empty());
+    }
+
+    #[test]
+    fn bob_is_added_last() {
+        let permows = builder(vec![], vec![], &RoomAccess { private: true, invitees: vec![] }).merge();
+        assert_eq!(permows.last().unwrap().kind, PermissionOverwriteType::Member(BOB));
+    }
+
+    #[test]
+    fn bob_overrides_the_category() {
+        let bob = PermissionOverwriteType::Member(BOB);
+        let category = vec![PermissionOverwrite { allow: Permissions::empty(), deny: Permissions::CONNECT, kind: bob }];
+        let permows = builder(category, vec![], &RoomAccess::default()).merge();
 
-        Ok(ChannelBuilderPermissionOverwrites::build(own_id, creator_id, category, preset))
+        // The overwrite keeps the position of the one of the category, but its permissions are Bob's
+        assert_eq!(permows.first().unwrap().kind, bob);
+        assert_eq!(find(&permows, bob).allow, voice());
+        assert_eq!(find(&permows, bob).deny, Permissions::empty());
     }
 }